@@ -0,0 +1,130 @@
+//! Runtime lookup of cluster and attribute definitions by their IDs, e.g. to name
+//! the contents of a received frame.
+
+use crate::types::{AttributeDescriptor, AttributeSide};
+
+/// A cluster with the type of its attributes erased, see [`crate::Cluster`] for the typed variant.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct ClusterDescriptor<'a> {
+    pub code: u16,
+    pub name: &'a str,
+    /// The manufacturer defining a private cluster, `None` for standard clusters
+    pub manufacturer_code: Option<u16>,
+    /// The attributes of the cluster sorted by ID and manufacturer code
+    pub attributes: &'a [AttributeDescriptor<'a>],
+    /// Clusters with the same ID which refine this one for specific device types,
+    /// e.g. with different ranges or mandatory attributes
+    pub variants: &'a [ClusterDescriptor<'a>],
+    /// The device types which use this variant instead of its base cluster, empty for
+    /// base clusters
+    pub device_types: &'a [u16],
+}
+
+impl<'a> ClusterDescriptor<'a> {
+    /// Finds an attribute of this cluster, `manufacturer_code` selects a manufacturer
+    /// specific attribute. Global attributes like `ClusterRevision` are found as well.
+    pub fn attribute(
+        &self,
+        code: u16,
+        manufacturer_code: Option<u16>,
+    ) -> Option<&'a AttributeDescriptor<'a>> {
+        find(self.attributes, code, manufacturer_code)
+            .or_else(|| find(crate::global::ATTRIBUTES, code, manufacturer_code))
+    }
+
+    /// The attributes implemented by the server or client side of this cluster
+    pub fn attributes_on(
+        &self,
+        side: AttributeSide,
+    ) -> impl Iterator<Item = &'a AttributeDescriptor<'a>> + 'a {
+        self.attributes
+            .iter()
+            .filter(move |x| x.side.applies_to(side))
+    }
+
+    /// Finds a variant of this cluster by its name.
+    pub fn variant(&self, name: &str) -> Option<&'a ClusterDescriptor<'a>> {
+        self.variants.iter().find(|x| x.name == name)
+    }
+
+    /// The variant of this cluster used by `device_type`, or this cluster itself if the
+    /// device type uses no variant.
+    pub fn for_device_type(&self, device_type: u16) -> &ClusterDescriptor<'a> {
+        self.variants
+            .iter()
+            .find(|x| x.device_types.contains(&device_type))
+            .unwrap_or(self)
+    }
+}
+
+/// All known clusters sorted by ID and manufacturer code. Variants are listed by
+/// their base cluster.
+pub const ALL: &[ClusterDescriptor<'static>] = crate::CLUSTER_TABLE;
+
+/// Finds a cluster by its ID. Standard clusters take precedence over private clusters
+/// of manufacturers using the same ID. Variants of the cluster are available through
+/// [`ClusterDescriptor::variants`], [`ClusterDescriptor::for_device_type`] selects the
+/// one used by a device type.
+pub fn by_id(code: u16) -> Option<&'static ClusterDescriptor<'static>> {
+    let i = ALL.partition_point(|x| x.code < code);
+    ALL.get(i).filter(|x| x.code == code)
+}
+
+/// Finds a cluster by its ID and the manufacturer defining it, `None` for standard clusters.
+pub fn by_manufacturer(
+    code: u16,
+    manufacturer_code: Option<u16>,
+) -> Option<&'static ClusterDescriptor<'static>> {
+    ALL.binary_search_by_key(&(code, manufacturer_code), |x| {
+        (x.code, x.manufacturer_code)
+    })
+    .ok()
+    .map(|i| &ALL[i])
+}
+
+/// Finds an attribute by the ID of its cluster and its own ID. With a `manufacturer_code`
+/// manufacturer specific attributes of both private and standard clusters are found.
+pub fn attribute(
+    cluster: u16,
+    code: u16,
+    manufacturer_code: Option<u16>,
+) -> Option<&'static AttributeDescriptor<'static>> {
+    by_manufacturer(cluster, manufacturer_code)
+        .or_else(|| by_id(cluster))?
+        .attribute(code, manufacturer_code)
+}
+
+fn find<'a>(
+    attributes: &'a [AttributeDescriptor<'a>],
+    code: u16,
+    manufacturer_code: Option<u16>,
+) -> Option<&'a AttributeDescriptor<'a>> {
+    attributes
+        .binary_search_by_key(&(code, manufacturer_code), |x| {
+            (x.code, x.manufacturer_code)
+        })
+        .ok()
+        .map(|i| &attributes[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_by_device_type() {
+        let level = by_id(0x0008).unwrap();
+        assert_eq!(level.name, "Level");
+        // Dimmable Light
+        assert_eq!(
+            level.for_device_type(0x0101).name,
+            "LevelControlForLighting"
+        );
+        // Window Covering Device
+        assert_eq!(level.for_device_type(0x0202).name, "Level");
+        assert_eq!(
+            level.variant("LevelControlForLighting"),
+            Some(level.for_device_type(0x010d))
+        );
+    }
+}
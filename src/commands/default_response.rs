@@ -0,0 +1,65 @@
+use crate::types::codec::{Reader, Writer};
+use crate::types::{ZclCompatibleType, ZclDecode, ZclEncode};
+use crate::{Status, ZclError};
+
+/// Default Response (0x0b)
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct DefaultResponse {
+    /// The command which is responded to
+    pub command_id: u8,
+    pub status: Status,
+}
+
+impl ZclCompatibleType for DefaultResponse {
+    fn len(&self) -> usize {
+        2
+    }
+}
+
+impl ZclEncode for DefaultResponse {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.command_id)?;
+        writer.u8(self.status.into())?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for DefaultResponse {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            command_id: reader.u8()?,
+            status: Status::from(reader.u8()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let response = DefaultResponse {
+            command_id: 0x02,
+            status: Status::UnsupportedAttribute,
+        };
+        let mut data = [0u8; 2];
+        assert_eq!(response.to_bytes(&mut data), Ok(2));
+        assert_eq!(data, [0x02, 0x86]);
+        assert_eq!(DefaultResponse::from_bytes(&data), Ok(response));
+        assert_eq!(
+            response.to_bytes(&mut [0u8; 1]),
+            Err(ZclError::Serialization)
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            DefaultResponse::from_bytes(&[0x02]),
+            Err(ZclError::Serialization)
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use super::{Record, Records};
+use crate::ZclError;
+use crate::types::codec::{Reader, Writer};
+use crate::types::{U8, ZclCompatibleType, ZclDecode, ZclEncode};
+
+/// Discover Attributes (0x0c), also the payload of Discover Attributes Extended (0x15)
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct DiscoverAttributes {
+    pub start_attribute_id: u16,
+    pub max_attribute_ids: u8,
+}
+
+impl ZclCompatibleType for DiscoverAttributes {
+    fn len(&self) -> usize {
+        3
+    }
+}
+
+impl ZclEncode for DiscoverAttributes {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.start_attribute_id)?;
+        writer.u8(self.max_attribute_ids)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for DiscoverAttributes {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            start_attribute_id: reader.u16()?,
+            max_attribute_ids: reader.u8()?,
+        })
+    }
+}
+
+/// Discover Commands Received (0x11) and Discover Commands Generated (0x13)
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct DiscoverCommands {
+    pub start_command_id: u8,
+    pub max_command_ids: u8,
+}
+
+impl ZclCompatibleType for DiscoverCommands {
+    fn len(&self) -> usize {
+        2
+    }
+}
+
+impl ZclEncode for DiscoverCommands {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.start_command_id)?;
+        writer.u8(self.max_command_ids)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for DiscoverCommands {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            start_command_id: reader.u8()?,
+            max_command_ids: reader.u8()?,
+        })
+    }
+}
+
+/// An attribute supported by the device, see [`DiscoverAttributesResponse`].
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct AttributeInformation {
+    pub attribute_id: u16,
+    pub data_type: u8,
+}
+
+impl ZclCompatibleType for AttributeInformation {
+    fn len(&self) -> usize {
+        3
+    }
+}
+
+impl ZclEncode for AttributeInformation {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.attribute_id)?;
+        writer.u8(self.data_type)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for AttributeInformation {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            attribute_id: reader.u16()?,
+            data_type: reader.u8()?,
+        })
+    }
+}
+
+impl Record<'_> for AttributeInformation {}
+
+/// An attribute supported by the device together with its access control,
+/// see [`DiscoverAttributesExtendedResponse`].
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct ExtendedAttributeInformation {
+    pub attribute_id: u16,
+    pub data_type: u8,
+    /// Bit 0 is set if the attribute is readable, bit 1 if it is writable and
+    /// bit 2 if it is reportable.
+    pub access_control: u8,
+}
+
+impl ZclCompatibleType for ExtendedAttributeInformation {
+    fn len(&self) -> usize {
+        4
+    }
+}
+
+impl ZclEncode for ExtendedAttributeInformation {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.attribute_id)?;
+        writer.u8(self.data_type)?;
+        writer.u8(self.access_control)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for ExtendedAttributeInformation {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            attribute_id: reader.u16()?,
+            data_type: reader.u8()?,
+            access_control: reader.u8()?,
+        })
+    }
+}
+
+impl Record<'_> for ExtendedAttributeInformation {}
+
+/// Defines a discovery response, which consists of a discovery complete flag
+/// followed by a list of records.
+macro_rules! discovery_response {
+    ($(#[$meta: meta])* $name: ident { $field: ident: $record: ty }) => {
+        $(#[$meta])*
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub struct $name<'a> {
+            /// Whether there are no more items left to discover
+            pub complete: bool,
+            pub $field: Records<'a, $record>,
+        }
+
+        impl ZclCompatibleType for $name<'_> {
+            fn len(&self) -> usize {
+                1 + self.$field.len()
+            }
+        }
+
+        impl ZclEncode for $name<'_> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                let mut writer = Writer::new(data);
+                writer.u8(self.complete as u8)?;
+                writer.value(&self.$field)?;
+                Ok(writer.finish())
+            }
+        }
+
+        impl<'a> ZclDecode<'a> for $name<'a> {
+            fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+                let mut reader = Reader::new(data);
+                let complete = match reader.u8()? {
+                    0x00 => false,
+                    0x01 => true,
+                    _ => return Err(ZclError::ValueOutOfRange),
+                };
+                Ok(Self {
+                    complete,
+                    $field: Records::from_bytes(reader.rest())?,
+                })
+            }
+        }
+    };
+}
+
+discovery_response! {
+    /// Discover Attributes Response (0x0d)
+    DiscoverAttributesResponse { records: AttributeInformation }
+}
+
+discovery_response! {
+    /// Discover Commands Received Response (0x12) and Discover Commands Generated Response (0x14)
+    DiscoverCommandsResponse { command_ids: U8 }
+}
+
+discovery_response! {
+    /// Discover Attributes Extended Response (0x16)
+    DiscoverAttributesExtendedResponse { records: ExtendedAttributeInformation }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_round_trip() {
+        let request = DiscoverAttributes {
+            start_attribute_id: 0x4000,
+            max_attribute_ids: 0x10,
+        };
+        let mut data = [0u8; 3];
+        assert_eq!(request.to_bytes(&mut data), Ok(3));
+        assert_eq!(data, [0x00, 0x40, 0x10]);
+        assert_eq!(DiscoverAttributes::from_bytes(&data), Ok(request));
+
+        let request = DiscoverCommands {
+            start_command_id: 0x40,
+            max_command_ids: 0x08,
+        };
+        assert_eq!(request.to_bytes(&mut data), Ok(2));
+        assert_eq!(data[..2], [0x40, 0x08]);
+        assert_eq!(DiscoverCommands::from_bytes(&data[..2]), Ok(request));
+    }
+
+    #[test]
+    fn attributes_response_round_trip() {
+        let records = [
+            AttributeInformation {
+                attribute_id: 0x0000,
+                data_type: 0x10,
+            },
+            AttributeInformation {
+                attribute_id: 0x4000,
+                data_type: 0x21,
+            },
+        ];
+        let response = DiscoverAttributesResponse {
+            complete: true,
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 8];
+        assert_eq!(response.len(), 7);
+        assert_eq!(response.to_bytes(&mut data), Ok(7));
+        assert_eq!(data[..7], [0x01, 0x00, 0x00, 0x10, 0x00, 0x40, 0x21]);
+        let decoded = DiscoverAttributesResponse::from_bytes(&data[..7]).unwrap();
+        assert!(decoded.complete);
+        assert!(decoded.records.iter().eq(records.iter().copied().map(Ok)));
+    }
+
+    #[test]
+    fn extended_attributes_response_round_trip() {
+        let records = [ExtendedAttributeInformation {
+            attribute_id: 0x0010,
+            data_type: 0x42,
+            access_control: 0x03,
+        }];
+        let response = DiscoverAttributesExtendedResponse {
+            complete: false,
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 5];
+        assert_eq!(response.to_bytes(&mut data), Ok(5));
+        assert_eq!(data, [0x00, 0x10, 0x00, 0x42, 0x03]);
+        let decoded = DiscoverAttributesExtendedResponse::from_bytes(&data).unwrap();
+        assert!(!decoded.complete);
+        assert!(decoded.records.iter().eq(records.iter().copied().map(Ok)));
+    }
+
+    #[test]
+    fn commands_response_round_trip() {
+        let ids = [U8(0x00), U8(0x01), U8(0x40)];
+        let response = DiscoverCommandsResponse {
+            complete: true,
+            command_ids: Records::Slice(&ids),
+        };
+        let mut data = [0u8; 4];
+        assert_eq!(response.to_bytes(&mut data), Ok(4));
+        assert_eq!(data, [0x01, 0x00, 0x01, 0x40]);
+        let decoded = DiscoverCommandsResponse::from_bytes(&data).unwrap();
+        assert!(decoded.command_ids.iter().eq(ids.iter().copied().map(Ok)));
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            DiscoverAttributes::from_bytes(&[0x00, 0x40]),
+            Err(ZclError::Serialization)
+        );
+        assert_eq!(
+            DiscoverCommands::from_bytes(&[0x40]),
+            Err(ZclError::Serialization)
+        );
+        assert_eq!(
+            DiscoverAttributesResponse::from_bytes(&[]),
+            Err(ZclError::Serialization)
+        );
+        // Only the complete flag is checked up front, the records when iterating
+        let response = DiscoverAttributesResponse::from_bytes(&[0x01, 0x00, 0x00]).unwrap();
+        let mut records = response.records.iter();
+        assert_eq!(records.next(), Some(Err(ZclError::Serialization)));
+        assert_eq!(records.next(), None);
+        assert_eq!(
+            DiscoverAttributesResponse::from_bytes(&[0x02]),
+            Err(ZclError::ValueOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Payloads of the global (profile-wide) commands, see [`crate::Command`].
+//!
+//! Every payload can be encoded into and decoded from the command payload
+//! following the [`FrameHeader`](crate::frame::FrameHeader). Lists of records
+//! are borrowed from the received buffer and decoded lazily on iteration.
+
+/// Defines a command payload which consists of a single list of records.
+/// Responses marked `compact` send a single success status instead of an
+/// empty list.
+macro_rules! records_command {
+    ($(#[$meta: meta])* $name: ident { $field: ident: $record: ty }) => {
+        records_command!(@define $(#[$meta])* $name, $field, $record);
+
+        impl ZclCompatibleType for $name<'_> {
+            fn len(&self) -> usize {
+                self.$field.len()
+            }
+        }
+
+        impl ZclEncode for $name<'_> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                self.$field.to_bytes(data)
+            }
+        }
+
+        impl<'a> ZclDecode<'a> for $name<'a> {
+            fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+                Ok(Self {
+                    $field: Records::from_bytes(data)?,
+                })
+            }
+        }
+    };
+    ($(#[$meta: meta])* compact $name: ident { $field: ident: $record: ty }) => {
+        records_command!(@define $(#[$meta])* $name, $field, $record);
+
+        impl ZclCompatibleType for $name<'_> {
+            fn len(&self) -> usize {
+                super::compact_len(&self.$field)
+            }
+        }
+
+        impl ZclEncode for $name<'_> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                super::compact_to_bytes(&self.$field, data)
+            }
+        }
+
+        impl<'a> ZclDecode<'a> for $name<'a> {
+            fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+                Ok(Self {
+                    $field: super::compact_from_bytes(data)?,
+                })
+            }
+        }
+    };
+    (@define $(#[$meta: meta])* $name: ident, $field: ident, $record: ty) => {
+        $(#[$meta])*
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub struct $name<'a> {
+            pub $field: Records<'a, $record>,
+        }
+    };
+}
+
+mod default_response;
+mod discover;
+mod read;
+mod reporting;
+mod structured;
+mod write;
+
+pub use default_response::*;
+pub use discover::*;
+pub use read::*;
+pub use reporting::*;
+pub use structured::*;
+pub use write::*;
+
+use crate::types::codec::{Reader, Writer};
+use crate::types::{U8, U16, ZclCompatibleType, ZclDecode, ZclEncode};
+use crate::{Status, ZclError};
+
+/// A record of a [`Records`] list.
+pub trait Record<'a>: ZclEncode + ZclDecode<'a> + Copy {
+    /// The length of the record at the start of `data` without decoding it, so that the
+    /// records following an invalid one can still be read. Fails if the length is
+    /// unknown, e.g. because a value has an unknown data type.
+    fn encoded_len(data: &'a [u8]) -> Result<usize, ZclError> {
+        Ok(Self::from_bytes(data)?.len())
+    }
+}
+
+/// A list of records which extends to the end of the command payload.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Records<'a, R> {
+    /// Records supplied by the application, e.g. for encoding
+    Slice(&'a [R]),
+    /// Records supplied by the application which are borrowed individually, e.g. from
+    /// the fields of constants
+    Refs(&'a [&'a R]),
+    /// Records borrowed from a received payload which are decoded on iteration
+    Encoded(&'a [u8]),
+}
+
+impl<'a, R: Record<'a>> Records<'a, R> {
+    /// Iterates over the records. Received records are decoded one at a time, an
+    /// invalid record yields an error and is skipped if its length is known.
+    /// Otherwise the iteration ends after the error.
+    pub fn iter(&self) -> RecordIter<'a, R> {
+        match *self {
+            Self::Slice(xs) => RecordIter::Slice(xs.iter()),
+            Self::Refs(xs) => RecordIter::Refs(xs.iter()),
+            Self::Encoded(data) => RecordIter::Encoded(data),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Slice(xs) => xs.is_empty(),
+            Self::Refs(xs) => xs.is_empty(),
+            Self::Encoded(data) => data.is_empty(),
+        }
+    }
+}
+
+impl<'a, R: Record<'a>> IntoIterator for Records<'a, R> {
+    type Item = Result<R, ZclError>;
+    type IntoIter = RecordIter<'a, R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, R: Record<'a>> ZclCompatibleType for Records<'a, R> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Slice(xs) => xs.iter().map(|x| x.len()).sum(),
+            Self::Refs(xs) => xs.iter().map(|x| x.len()).sum(),
+            Self::Encoded(data) => data.len(),
+        }
+    }
+}
+
+impl<'a, R: Record<'a>> ZclEncode for Records<'a, R> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        match self {
+            Self::Slice(xs) => {
+                for x in xs.iter() {
+                    writer.value(x)?;
+                }
+            }
+            Self::Refs(xs) => {
+                for x in xs.iter() {
+                    writer.value(*x)?;
+                }
+            }
+            Self::Encoded(records) => writer.bytes(records)?,
+        }
+        Ok(writer.finish())
+    }
+}
+
+impl<'a, R: Record<'a>> ZclDecode<'a> for Records<'a, R> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        Ok(Self::Encoded(data))
+    }
+}
+
+/// Iterator over a list of records, see [`Records::iter`].
+pub enum RecordIter<'a, R> {
+    Slice(core::slice::Iter<'a, R>),
+    Refs(core::slice::Iter<'a, &'a R>),
+    Encoded(&'a [u8]),
+}
+
+impl<'a, R: Record<'a>> Iterator for RecordIter<'a, R> {
+    type Item = Result<R, ZclError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Slice(xs) => xs.next().copied().map(Ok),
+            Self::Refs(xs) => xs.next().map(|x| Ok(**x)),
+            Self::Encoded(data) => {
+                if data.is_empty() {
+                    return None;
+                }
+                let record = R::from_bytes(data);
+                // The rest of the payload cannot be read if the length is unknown
+                let len = match record {
+                    Ok(ref x) => x.len(),
+                    Err(_) => R::encoded_len(data).unwrap_or(data.len()),
+                };
+                *data = data.get(len..).unwrap_or_default();
+                Some(record)
+            }
+        }
+    }
+}
+
+// Attribute IDs are listed as plain integers, e.g. in Read Attributes
+
+impl ZclCompatibleType for u16 {
+    fn len(&self) -> usize {
+        2
+    }
+}
+
+impl ZclEncode for u16 {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(*self)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for u16 {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        Reader::new(data).u16()
+    }
+}
+
+impl Record<'_> for u16 {}
+impl Record<'_> for U8 {}
+impl Record<'_> for U16 {}
+
+/// A record of a response which only lists failures, see [`compact_to_bytes`].
+pub(crate) trait StatusRecord {
+    fn status(&self) -> Status;
+}
+
+/// The records of a compact response which are sent, i.e. all but the successes.
+/// Received records are kept as they are.
+fn failures<'a, R: Record<'a> + StatusRecord>(records: &Records<'a, R>) -> impl Iterator<Item = R> {
+    records
+        .iter()
+        .filter_map(Result::ok)
+        .filter(|x| x.status() != Status::Success)
+}
+
+/// Encodes a response whose records only list failures. If there are none, a
+/// single success status is sent instead.
+fn compact_to_bytes<'a, R: Record<'a> + StatusRecord>(
+    records: &Records<'a, R>,
+    data: &mut [u8],
+) -> Result<usize, ZclError> {
+    let mut writer = Writer::new(data);
+    match records {
+        Records::Encoded(bytes) if !bytes.is_empty() => writer.bytes(bytes)?,
+        _ if failures(records).next().is_none() => writer.u8(Status::Success.into())?,
+        _ => {
+            for x in failures(records) {
+                writer.value(&x)?;
+            }
+        }
+    }
+    Ok(writer.finish())
+}
+
+/// Decodes a response whose records only list failures, see [`compact_to_bytes`].
+fn compact_from_bytes<'a, R: Record<'a> + StatusRecord>(
+    data: &'a [u8],
+) -> Result<Records<'a, R>, ZclError> {
+    if data == [u8::from(Status::Success)] {
+        Ok(Records::Encoded(&[]))
+    } else {
+        Ok(Records::Encoded(data))
+    }
+}
+
+fn compact_len<'a, R: Record<'a> + StatusRecord>(records: &Records<'a, R>) -> usize {
+    match records {
+        Records::Encoded(bytes) if !bytes.is_empty() => bytes.len(),
+        // The single success status if there are no failures
+        _ => failures(records).map(|x| x.len()).sum::<usize>().max(1),
+    }
+}
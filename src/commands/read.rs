@@ -0,0 +1,213 @@
+use super::{Record, Records};
+use crate::types::codec::{Reader, Writer};
+use crate::types::{Attribute, ZclCompatibleType, ZclDecode, ZclEncode, ZclType, ZclValue};
+use crate::{Status, ZclError};
+
+records_command! {
+    /// Read Attributes (0x00)
+    ReadAttributes { ids: u16 }
+}
+
+impl<'a> ReadAttributes<'a> {
+    /// Reads the attributes with the given IDs, which are typically borrowed from the
+    /// attribute constants, e.g. `ReadAttributes::new(&[&basic::ZCL_VERSION.code])`.
+    pub fn new(ids: &'a [&'a u16]) -> Self {
+        Self {
+            ids: Records::Refs(ids),
+        }
+    }
+}
+
+/// A single attribute of a [`ReadAttributesResponse`]. The value is only present
+/// if the attribute was read successfully, otherwise the failure status is given.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct ReadAttributeStatusRecord<'a> {
+    pub attribute_id: u16,
+    pub result: Result<ZclValue<'a>, Status>,
+}
+
+impl ZclCompatibleType for ReadAttributeStatusRecord<'_> {
+    fn len(&self) -> usize {
+        match self.result {
+            Ok(value) => 4 + value.len(),
+            Err(_) => 3,
+        }
+    }
+}
+
+impl ZclEncode for ReadAttributeStatusRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.attribute_id)?;
+        match self.result {
+            Ok(value) => {
+                writer.u8(Status::Success.into())?;
+                writer.u8(value.type_id())?;
+                writer.value(&value)?;
+            }
+            // A success status must be accompanied by a value
+            Err(Status::Success) => return Err(ZclError::ValueOutOfRange),
+            Err(status) => writer.u8(status.into())?,
+        }
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for ReadAttributeStatusRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        let attribute_id = reader.u16()?;
+        let result = match Status::from(reader.u8()?) {
+            Status::Success => {
+                let type_id = reader.u8()?;
+                Ok(reader.zcl_value(type_id)?)
+            }
+            status => Err(status),
+        };
+        Ok(Self {
+            attribute_id,
+            result,
+        })
+    }
+}
+
+impl<'a> Record<'a> for ReadAttributeStatusRecord<'a> {
+    fn encoded_len(data: &'a [u8]) -> Result<usize, ZclError> {
+        let mut reader = Reader::new(data);
+        reader.u16()?;
+        if Status::from(reader.u8()?) == Status::Success {
+            let type_id = reader.u8()?;
+            reader.skip_value(type_id)?;
+        }
+        Ok(reader.finish())
+    }
+}
+
+records_command! {
+    /// Read Attributes Response (0x01)
+    ReadAttributesResponse { records: ReadAttributeStatusRecord<'a> }
+}
+
+impl<'a> ReadAttributesResponse<'a> {
+    pub fn new(records: &'a [ReadAttributeStatusRecord<'a>]) -> Self {
+        Self {
+            records: Records::Slice(records),
+        }
+    }
+
+    /// The value of the attribute with the ID `code`, `None` if it is missing from the
+    /// response, could not be read or its record is invalid.
+    pub fn value(&self, code: u16) -> Option<ZclValue<'a>> {
+        self.records
+            .iter()
+            .filter_map(Result::ok)
+            .find(|x| x.attribute_id == code)?
+            .result
+            .ok()
+    }
+
+    /// The value of `attribute`, `None` if it is missing from the response, could not be
+    /// read or was sent with a different data type.
+    pub fn get<T>(&self, attribute: &Attribute<'_, T>) -> Option<T>
+    where
+        T: ZclType + TryFrom<ZclValue<'a>, Error = ZclError>,
+    {
+        let value = self.value(attribute.code)?;
+        if value.type_id() != T::ID {
+            return None;
+        }
+        T::try_from(value).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::general::basic;
+    use crate::types::{Enum8, LossyCharacterString, U8};
+
+    #[test]
+    fn read_attributes_from_constants() {
+        let ids = [&basic::ZCL_VERSION.code, &basic::POWER_SOURCE.code];
+        let command = ReadAttributes::new(&ids);
+        assert_eq!(command.len(), 4);
+        let mut data = [0u8; 8];
+        assert_eq!(command.to_bytes(&mut data), Ok(4));
+        assert_eq!(data[..4], [0x00, 0x00, 0x07, 0x00]);
+        let decoded = ReadAttributes::from_bytes(&data[..4]).unwrap();
+        let mut ids = decoded.ids.iter();
+        assert_eq!(ids.next(), Some(Ok(0x0000)));
+        assert_eq!(ids.next(), Some(Ok(0x0007)));
+        assert_eq!(ids.next(), None);
+    }
+
+    #[test]
+    fn typed_lookup() {
+        let data = [
+            0x00, 0x00, 0x00, 0x20, 0x08, // ZclVersion
+            0x07, 0x00, 0x00, 0x21, 0x01, 0x00, // PowerSource with the wrong data type
+            0x10, 0x00, 0x86, // LocationDescription, unsupported
+        ];
+        let response = ReadAttributesResponse::from_bytes(&data).unwrap();
+        assert_eq!(response.get(&basic::ZCL_VERSION), Some(U8(8)));
+        assert_eq!(response.get(&basic::POWER_SOURCE), None);
+        assert_eq!(response.get(&basic::LOCATION_DESCRIPTION), None);
+        assert_eq!(response.get(&basic::HW_VERSION), None);
+        assert_eq!(
+            response.records.iter().nth(2).unwrap(),
+            Ok(ReadAttributeStatusRecord {
+                attribute_id: 0x0010,
+                result: Err(Status::UnsupportedAttribute),
+            })
+        );
+    }
+
+    #[test]
+    fn response_with_latin1_string() {
+        let data = [
+            0x04, 0x00, 0x00, 0x42, 3, b'I', 0xe9, b'A', // ManufacturerName
+            0x00, 0x00, 0x00, 0x20, 0x08, // ZclVersion
+        ];
+        let response = ReadAttributesResponse::from_bytes(&data).unwrap();
+        assert_eq!(
+            response.value(0x0004),
+            Some(ZclValue::LossyCharacterString(LossyCharacterString(Some(
+                &data[5..8]
+            ))))
+        );
+        assert_eq!(response.value(0x0000), Some(ZclValue::U8(U8(8))));
+    }
+
+    #[test]
+    fn invalid_records_are_skipped() {
+        let data = [
+            0x00, 0x00, 0x00, 0x20, 0x08, // ZclVersion
+            0x00, 0x40, 0x00, 0x10, 0x02, // GlobalSceneControl, not a boolean
+            0x07, 0x00, 0x00, 0x30, 0x01, // PowerSource
+        ];
+        let response = ReadAttributesResponse::from_bytes(&data).unwrap();
+        let mut records = response.records.iter();
+        assert_eq!(records.next().unwrap().unwrap().attribute_id, 0x0000);
+        assert_eq!(records.next(), Some(Err(ZclError::ValueOutOfRange)));
+        assert_eq!(records.next().unwrap().unwrap().attribute_id, 0x0007);
+        assert_eq!(records.next(), None);
+        assert_eq!(response.value(0x4000), None);
+        assert_eq!(response.value(0x0007), Some(ZclValue::Enum8(Enum8(1))));
+    }
+
+    #[test]
+    fn records_after_unknown_type_are_lost() {
+        let data = [
+            0x00, 0x00, 0x00, 0x20, 0x08, // ZclVersion
+            0x01, 0x00, 0x00, 0x3f, 0x01, // ApplicationVersion, unknown data type
+            0x07, 0x00, 0x00, 0x30, 0x01, // PowerSource
+        ];
+        let response = ReadAttributesResponse::from_bytes(&data).unwrap();
+        let mut records = response.records.iter();
+        assert!(records.next().unwrap().is_ok());
+        assert_eq!(records.next(), Some(Err(ZclError::UnknownDataType(0x3f))));
+        assert_eq!(records.next(), None);
+        assert_eq!(response.value(0x0000), Some(ZclValue::U8(U8(8))));
+        assert_eq!(response.value(0x0007), None);
+    }
+}
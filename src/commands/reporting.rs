@@ -0,0 +1,533 @@
+use super::{AttributeRecord, Record, Records, StatusRecord};
+use crate::types::codec::{Reader, Writer};
+use crate::types::{
+    Attribute, DataClass, ZclCompatibleType, ZclDecode, ZclEncode, ZclType, ZclValue, data_class,
+};
+use crate::{Status, ZclError};
+
+/// Whether the reportable change field is present for the data type, which is
+/// only the case for analog data types.
+fn is_analog(type_id: u8) -> bool {
+    data_class(type_id) == Some(DataClass::Analog)
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[repr(u8)]
+pub enum ReportingDirection {
+    /// The recipient of the configuration reports the attribute
+    Reported = 0x00,
+    /// The recipient of the configuration receives reports of the attribute
+    Received = 0x01,
+}
+
+impl TryFrom<u8> for ReportingDirection {
+    type Error = ZclError;
+
+    fn try_from(value: u8) -> Result<Self, ZclError> {
+        match value {
+            0x00 => Ok(Self::Reported),
+            0x01 => Ok(Self::Received),
+            _ => Err(ZclError::ValueOutOfRange),
+        }
+    }
+}
+
+/// How an attribute is reported, depending on the [`ReportingDirection`].
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ReportingConfiguration<'a> {
+    Reported {
+        data_type: u8,
+        /// Minimum interval between reports in seconds
+        min_interval: u16,
+        /// Maximum interval between reports in seconds. 0xffff disables periodic
+        /// reporting and 0x0000 reports changes only.
+        max_interval: u16,
+        /// The change which triggers a report. Present if and only if the data
+        /// type is analog.
+        reportable_change: Option<ZclValue<'a>>,
+    },
+    Received {
+        /// The maximum expected time between reports in seconds, 0 if unchecked
+        timeout: u16,
+    },
+}
+
+impl ReportingConfiguration<'_> {
+    pub fn direction(&self) -> ReportingDirection {
+        match self {
+            Self::Reported { .. } => ReportingDirection::Reported,
+            Self::Received { .. } => ReportingDirection::Received,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Reported {
+                reportable_change, ..
+            } => 5 + reportable_change.map_or(0, |x| x.len()),
+            Self::Received { .. } => 2,
+        }
+    }
+
+    fn encode(&self, writer: &mut Writer) -> Result<(), ZclError> {
+        match *self {
+            Self::Reported {
+                data_type,
+                min_interval,
+                max_interval,
+                reportable_change,
+            } => {
+                writer.u8(data_type)?;
+                writer.u16(min_interval)?;
+                writer.u16(max_interval)?;
+                match reportable_change {
+                    Some(change) if is_analog(data_type) && change.type_id() == data_type => {
+                        writer.value(&change)?
+                    }
+                    None if !is_analog(data_type) => {}
+                    _ => return Err(ZclError::TypeMismatch),
+                }
+            }
+            Self::Received { timeout } => writer.u16(timeout)?,
+        }
+        Ok(())
+    }
+
+    fn decode<'a>(
+        direction: ReportingDirection,
+        reader: &mut Reader<'a>,
+    ) -> Result<ReportingConfiguration<'a>, ZclError> {
+        Ok(match direction {
+            ReportingDirection::Reported => {
+                let data_type = reader.u8()?;
+                ReportingConfiguration::Reported {
+                    data_type,
+                    min_interval: reader.u16()?,
+                    max_interval: reader.u16()?,
+                    reportable_change: if is_analog(data_type) {
+                        Some(reader.zcl_value(data_type)?)
+                    } else {
+                        None
+                    },
+                }
+            }
+            ReportingDirection::Received => ReportingConfiguration::Received {
+                timeout: reader.u16()?,
+            },
+        })
+    }
+
+    /// Skips a configuration without decoding the reportable change, see [`Record::encoded_len`].
+    fn skip(direction: ReportingDirection, reader: &mut Reader) -> Result<(), ZclError> {
+        match direction {
+            ReportingDirection::Reported => {
+                let data_type = reader.u8()?;
+                reader.u16()?;
+                reader.u16()?;
+                if is_analog(data_type) {
+                    reader.skip_value(data_type)?;
+                }
+            }
+            ReportingDirection::Received => {
+                reader.u16()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reporting configuration of a single attribute, see [`ConfigureReporting`].
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct AttributeReportingConfigurationRecord<'a> {
+    pub attribute_id: u16,
+    pub configuration: ReportingConfiguration<'a>,
+}
+
+impl<'a> AttributeReportingConfigurationRecord<'a> {
+    /// Configures the recipient to report `attribute`. The reportable change must be
+    /// given if and only if the data type is analog, otherwise this fails with
+    /// [`Status::InvalidDataType`]. Fails with [`Status::UnreportableAttribute`] if
+    /// the attribute is not reportable.
+    pub fn reported<T>(
+        attribute: &Attribute<'_, T>,
+        min_interval: u16,
+        max_interval: u16,
+        reportable_change: Option<T>,
+    ) -> Result<Self, Status>
+    where
+        T: ZclType + Into<ZclValue<'a>>,
+    {
+        if !attribute.reportable {
+            return Err(Status::UnreportableAttribute);
+        }
+        if reportable_change.is_some() != (T::CLASS == DataClass::Analog) {
+            return Err(Status::InvalidDataType);
+        }
+        Ok(Self {
+            attribute_id: attribute.code,
+            configuration: ReportingConfiguration::Reported {
+                data_type: T::ID,
+                min_interval,
+                max_interval,
+                reportable_change: reportable_change.map(Into::into),
+            },
+        })
+    }
+
+    /// Configures the recipient to expect reports of `attribute` at least every
+    /// `timeout` seconds. Fails with [`Status::UnreportableAttribute`] if the
+    /// attribute is not reportable.
+    pub fn received<T: ZclType>(
+        attribute: &Attribute<'_, T>,
+        timeout: u16,
+    ) -> Result<Self, Status> {
+        if !attribute.reportable {
+            return Err(Status::UnreportableAttribute);
+        }
+        Ok(Self {
+            attribute_id: attribute.code,
+            configuration: ReportingConfiguration::Received { timeout },
+        })
+    }
+}
+
+impl ZclCompatibleType for AttributeReportingConfigurationRecord<'_> {
+    fn len(&self) -> usize {
+        3 + self.configuration.len()
+    }
+}
+
+impl ZclEncode for AttributeReportingConfigurationRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.configuration.direction() as u8)?;
+        writer.u16(self.attribute_id)?;
+        self.configuration.encode(&mut writer)?;
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for AttributeReportingConfigurationRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        let direction = ReportingDirection::try_from(reader.u8()?)?;
+        Ok(Self {
+            attribute_id: reader.u16()?,
+            configuration: ReportingConfiguration::decode(direction, &mut reader)?,
+        })
+    }
+}
+
+impl<'a> Record<'a> for AttributeReportingConfigurationRecord<'a> {
+    fn encoded_len(data: &'a [u8]) -> Result<usize, ZclError> {
+        let mut reader = Reader::new(data);
+        let direction = ReportingDirection::try_from(reader.u8()?)?;
+        reader.u16()?;
+        ReportingConfiguration::skip(direction, &mut reader)?;
+        Ok(reader.finish())
+    }
+}
+
+records_command! {
+    /// Configure Reporting (0x06)
+    ConfigureReporting { records: AttributeReportingConfigurationRecord<'a> }
+}
+
+/// An attribute whose reporting could not be configured, see [`ConfigureReportingResponse`].
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct AttributeStatusRecord {
+    pub status: Status,
+    pub direction: ReportingDirection,
+    pub attribute_id: u16,
+}
+
+impl ZclCompatibleType for AttributeStatusRecord {
+    fn len(&self) -> usize {
+        4
+    }
+}
+
+impl ZclEncode for AttributeStatusRecord {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.status.into())?;
+        writer.u8(self.direction as u8)?;
+        writer.u16(self.attribute_id)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for AttributeStatusRecord {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            status: Status::from(reader.u8()?),
+            direction: ReportingDirection::try_from(reader.u8()?)?,
+            attribute_id: reader.u16()?,
+        })
+    }
+}
+
+impl Record<'_> for AttributeStatusRecord {}
+
+impl StatusRecord for AttributeStatusRecord {
+    fn status(&self) -> Status {
+        self.status
+    }
+}
+
+records_command! {
+    /// Configure Reporting Response (0x07)
+    ///
+    /// Only attributes which could not be configured are sent, records with a
+    /// success status are left out. If all configurations succeeded, a single
+    /// success status is sent and the decoded records are empty.
+    compact ConfigureReportingResponse { records: AttributeStatusRecord }
+}
+
+/// An attribute whose reporting configuration is requested, see [`ReadReportingConfiguration`].
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct ReadReportingConfigurationRecord {
+    pub direction: ReportingDirection,
+    pub attribute_id: u16,
+}
+
+impl ZclCompatibleType for ReadReportingConfigurationRecord {
+    fn len(&self) -> usize {
+        3
+    }
+}
+
+impl ZclEncode for ReadReportingConfigurationRecord {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.direction as u8)?;
+        writer.u16(self.attribute_id)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for ReadReportingConfigurationRecord {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            direction: ReportingDirection::try_from(reader.u8()?)?,
+            attribute_id: reader.u16()?,
+        })
+    }
+}
+
+impl Record<'_> for ReadReportingConfigurationRecord {}
+
+records_command! {
+    /// Read Reporting Configuration (0x08)
+    ReadReportingConfiguration { records: ReadReportingConfigurationRecord }
+}
+
+/// The reporting configuration of a single attribute, see
+/// [`ReadReportingConfigurationResponse`]. The configuration is only present if
+/// it could be read, otherwise the failure status is given.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct ReportingConfigurationStatusRecord<'a> {
+    pub direction: ReportingDirection,
+    pub attribute_id: u16,
+    pub result: Result<ReportingConfiguration<'a>, Status>,
+}
+
+impl ZclCompatibleType for ReportingConfigurationStatusRecord<'_> {
+    fn len(&self) -> usize {
+        4 + self.result.map_or(0, |x| x.len())
+    }
+}
+
+impl ZclEncode for ReportingConfigurationStatusRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        match self.result {
+            Ok(configuration) if configuration.direction() == self.direction => {
+                writer.u8(Status::Success.into())?;
+                writer.u8(self.direction as u8)?;
+                writer.u16(self.attribute_id)?;
+                configuration.encode(&mut writer)?;
+            }
+            Ok(_) | Err(Status::Success) => return Err(ZclError::ValueOutOfRange),
+            Err(status) => {
+                writer.u8(status.into())?;
+                writer.u8(self.direction as u8)?;
+                writer.u16(self.attribute_id)?;
+            }
+        }
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for ReportingConfigurationStatusRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        let status = Status::from(reader.u8()?);
+        let direction = ReportingDirection::try_from(reader.u8()?)?;
+        let attribute_id = reader.u16()?;
+        let result = match status {
+            Status::Success => Ok(ReportingConfiguration::decode(direction, &mut reader)?),
+            status => Err(status),
+        };
+        Ok(Self {
+            direction,
+            attribute_id,
+            result,
+        })
+    }
+}
+
+impl<'a> Record<'a> for ReportingConfigurationStatusRecord<'a> {
+    fn encoded_len(data: &'a [u8]) -> Result<usize, ZclError> {
+        let mut reader = Reader::new(data);
+        let status = Status::from(reader.u8()?);
+        let direction = ReportingDirection::try_from(reader.u8()?)?;
+        reader.u16()?;
+        if status == Status::Success {
+            ReportingConfiguration::skip(direction, &mut reader)?;
+        }
+        Ok(reader.finish())
+    }
+}
+
+records_command! {
+    /// Read Reporting Configuration Response (0x09)
+    ReadReportingConfigurationResponse { records: ReportingConfigurationStatusRecord<'a> }
+}
+
+records_command! {
+    /// Report Attributes (0x0a)
+    ReportAttributes { records: AttributeRecord<'a> }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::general::{basic, level, on_off};
+    use crate::types::{Bool, LossyCharacterString, U8};
+
+    #[test]
+    fn configuration_of_unreportable_attributes_is_refused() {
+        assert_eq!(
+            AttributeReportingConfigurationRecord::reported(
+                &basic::ZCL_VERSION,
+                1,
+                60,
+                Some(U8(1))
+            ),
+            Err(Status::UnreportableAttribute)
+        );
+        assert_eq!(
+            AttributeReportingConfigurationRecord::received(&basic::ZCL_VERSION, 60),
+            Err(Status::UnreportableAttribute)
+        );
+    }
+
+    #[test]
+    fn reportable_change_only_for_analog_types() {
+        assert_eq!(
+            AttributeReportingConfigurationRecord::reported(
+                &on_off::ON_OFF,
+                1,
+                60,
+                Some(Bool(Some(true)))
+            ),
+            Err(Status::InvalidDataType)
+        );
+        assert_eq!(
+            AttributeReportingConfigurationRecord::reported(&level::CURRENT_LEVEL, 1, 60, None),
+            Err(Status::InvalidDataType)
+        );
+        let record = AttributeReportingConfigurationRecord::reported(
+            &level::CURRENT_LEVEL,
+            1,
+            60,
+            Some(U8(5)),
+        )
+        .unwrap();
+        let records = [record];
+        let command = ConfigureReporting {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 16];
+        assert_eq!(command.to_bytes(&mut data), Ok(9));
+        assert_eq!(
+            data[..9],
+            [0x00, 0x00, 0x00, 0x20, 0x01, 0x00, 0x3c, 0x00, 0x05]
+        );
+        let decoded = ConfigureReporting::from_bytes(&data[..9]).unwrap();
+        assert_eq!(decoded.records.iter().next(), Some(Ok(record)));
+        assert_eq!(
+            AttributeReportingConfigurationRecord::received(&on_off::ON_OFF, 300),
+            Ok(AttributeReportingConfigurationRecord {
+                attribute_id: 0x0000,
+                configuration: ReportingConfiguration::Received { timeout: 300 },
+            })
+        );
+    }
+
+    #[test]
+    fn read_reporting_configuration_response_round_trip() {
+        let records = [
+            ReportingConfigurationStatusRecord {
+                direction: ReportingDirection::Reported,
+                attribute_id: 0x0000,
+                result: Ok(ReportingConfiguration::Reported {
+                    data_type: 0x20,
+                    min_interval: 1,
+                    max_interval: 60,
+                    reportable_change: Some(ZclValue::U8(U8(5))),
+                }),
+            },
+            ReportingConfigurationStatusRecord {
+                direction: ReportingDirection::Reported,
+                attribute_id: 0x4000,
+                result: Ok(ReportingConfiguration::Reported {
+                    data_type: 0x10,
+                    min_interval: 0,
+                    max_interval: 0xffff,
+                    reportable_change: None,
+                }),
+            },
+            ReportingConfigurationStatusRecord {
+                direction: ReportingDirection::Received,
+                attribute_id: 0x0001,
+                result: Err(Status::UnreportableAttribute),
+            },
+        ];
+        let response = ReadReportingConfigurationResponse {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 32];
+        let len = response.to_bytes(&mut data).unwrap();
+        assert_eq!(len, response.len());
+        assert_eq!(
+            data[..len],
+            [
+                0x00, 0x00, 0x00, 0x00, 0x20, 0x01, 0x00, 0x3c, 0x00, 0x05, // analog
+                0x00, 0x00, 0x00, 0x40, 0x10, 0x00, 0x00, 0xff, 0xff, // discrete
+                0x8c, 0x01, 0x01, 0x00, // failure
+            ]
+        );
+        let decoded = ReadReportingConfigurationResponse::from_bytes(&data[..len]).unwrap();
+        assert!(decoded.records.iter().eq(records.iter().copied().map(Ok)));
+    }
+
+    #[test]
+    fn report_with_binary_string() {
+        let data = [0x01, 0xff, 0x42, 4, 0x01, 0x21, 0xd1, 0x0b];
+        let report = ReportAttributes::from_bytes(&data).unwrap();
+        let record = report.records.iter().next().unwrap().unwrap();
+        assert_eq!(record.attribute_id, basic::LUMI_REPORT.code);
+        assert_eq!(
+            basic::LUMI_REPORT.validate_value(record.value, |_| None),
+            Ok(())
+        );
+        assert_eq!(
+            LossyCharacterString::try_from(record.value),
+            Ok(LossyCharacterString(Some(&data[4..])))
+        );
+    }
+}
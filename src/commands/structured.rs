@@ -0,0 +1,315 @@
+use super::{Record, Records, StatusRecord};
+use crate::types::codec::{Reader, Writer};
+use crate::types::{U16, ZclCompatibleType, ZclDecode, ZclEncode, ZclValue};
+use crate::{Status, ZclError};
+
+/// Selects an element of a structured attribute (array, structure, set or bag).
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Selector<'a> {
+    /// The lower nibble holds the number of indices. When writing to a set or a bag,
+    /// the upper nibble is 0x1 to add and 0x2 to remove the element.
+    pub indicator: u8,
+    /// One index per nesting level, starting with the outermost collection
+    pub indices: Records<'a, U16>,
+}
+
+impl ZclCompatibleType for Selector<'_> {
+    fn len(&self) -> usize {
+        1 + self.indices.len()
+    }
+}
+
+impl ZclEncode for Selector<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        if self.indices.iter().count() != (self.indicator & 0x0f) as usize {
+            return Err(ZclError::ValueOutOfRange);
+        }
+        let mut writer = Writer::new(data);
+        writer.u8(self.indicator)?;
+        writer.value(&self.indices)?;
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for Selector<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        let indicator = reader.u8()?;
+        Ok(Self {
+            indicator,
+            indices: Records::Encoded(reader.bytes(2 * (indicator & 0x0f) as usize)?),
+        })
+    }
+}
+
+/// An element of an attribute to read, see [`ReadAttributesStructured`].
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct StructuredReadRecord<'a> {
+    pub attribute_id: u16,
+    pub selector: Selector<'a>,
+}
+
+impl ZclCompatibleType for StructuredReadRecord<'_> {
+    fn len(&self) -> usize {
+        2 + self.selector.len()
+    }
+}
+
+impl ZclEncode for StructuredReadRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.attribute_id)?;
+        writer.value(&self.selector)?;
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for StructuredReadRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            attribute_id: reader.u16()?,
+            selector: reader.value()?,
+        })
+    }
+}
+
+impl<'a> Record<'a> for StructuredReadRecord<'a> {}
+
+records_command! {
+    /// Read Attributes Structured (0x0e)
+    ///
+    /// The response is a regular [`ReadAttributesResponse`](super::ReadAttributesResponse).
+    ReadAttributesStructured { records: StructuredReadRecord<'a> }
+}
+
+/// An element of an attribute together with its new value, see [`WriteAttributesStructured`].
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct StructuredWriteRecord<'a> {
+    pub attribute_id: u16,
+    pub selector: Selector<'a>,
+    pub value: ZclValue<'a>,
+}
+
+impl ZclCompatibleType for StructuredWriteRecord<'_> {
+    fn len(&self) -> usize {
+        3 + self.selector.len() + self.value.len()
+    }
+}
+
+impl ZclEncode for StructuredWriteRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.attribute_id)?;
+        writer.value(&self.selector)?;
+        writer.u8(self.value.type_id())?;
+        writer.value(&self.value)?;
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for StructuredWriteRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        let attribute_id = reader.u16()?;
+        let selector = reader.value()?;
+        let type_id = reader.u8()?;
+        Ok(Self {
+            attribute_id,
+            selector,
+            value: reader.zcl_value(type_id)?,
+        })
+    }
+}
+
+impl<'a> Record<'a> for StructuredWriteRecord<'a> {
+    fn encoded_len(data: &'a [u8]) -> Result<usize, ZclError> {
+        let mut reader = Reader::new(data);
+        reader.u16()?;
+        reader.value::<Selector>()?;
+        let type_id = reader.u8()?;
+        reader.skip_value(type_id)?;
+        Ok(reader.finish())
+    }
+}
+
+records_command! {
+    /// Write Attributes Structured (0x0f)
+    WriteAttributesStructured { records: StructuredWriteRecord<'a> }
+}
+
+/// An element of an attribute which could not be written, see
+/// [`WriteAttributesStructuredResponse`].
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct StructuredWriteStatusRecord<'a> {
+    pub status: Status,
+    pub attribute_id: u16,
+    pub selector: Selector<'a>,
+}
+
+impl ZclCompatibleType for StructuredWriteStatusRecord<'_> {
+    fn len(&self) -> usize {
+        3 + self.selector.len()
+    }
+}
+
+impl ZclEncode for StructuredWriteStatusRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.status.into())?;
+        writer.u16(self.attribute_id)?;
+        writer.value(&self.selector)?;
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for StructuredWriteStatusRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            status: Status::from(reader.u8()?),
+            attribute_id: reader.u16()?,
+            selector: reader.value()?,
+        })
+    }
+}
+
+impl<'a> Record<'a> for StructuredWriteStatusRecord<'a> {}
+
+impl StatusRecord for StructuredWriteStatusRecord<'_> {
+    fn status(&self) -> Status {
+        self.status
+    }
+}
+
+records_command! {
+    /// Write Attributes Structured Response (0x10)
+    ///
+    /// Only elements which could not be written are sent, records with a success
+    /// status are left out. If all writes succeeded, a single success status is
+    /// sent and the decoded records are empty.
+    compact WriteAttributesStructuredResponse { records: StructuredWriteStatusRecord<'a> }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::U8;
+
+    /// Compares records whose selectors may be borrowed or received
+    fn same_selector(a: &Selector, b: &Selector) -> bool {
+        a.indicator == b.indicator && a.indices.iter().eq(b.indices.iter())
+    }
+
+    #[test]
+    fn read_structured_round_trip() {
+        let indices = [U16(2), U16(1)];
+        let record = StructuredReadRecord {
+            attribute_id: 0x0010,
+            selector: Selector {
+                indicator: 0x02,
+                indices: Records::Slice(&indices),
+            },
+        };
+        let records = [record];
+        let command = ReadAttributesStructured {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 8];
+        assert_eq!(command.len(), 7);
+        assert_eq!(command.to_bytes(&mut data), Ok(7));
+        assert_eq!(data[..7], [0x10, 0x00, 0x02, 0x02, 0x00, 0x01, 0x00]);
+        let decoded = ReadAttributesStructured::from_bytes(&data[..7]).unwrap();
+        let mut records = decoded.records.iter();
+        let decoded = records.next().unwrap().unwrap();
+        assert_eq!(decoded.attribute_id, 0x0010);
+        assert!(same_selector(&decoded.selector, &record.selector));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn selector_must_match_its_indices() {
+        let indices = [U16(1)];
+        let selector = Selector {
+            indicator: 0x02,
+            indices: Records::Slice(&indices),
+        };
+        assert_eq!(
+            selector.to_bytes(&mut [0u8; 8]),
+            Err(ZclError::ValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn write_structured_round_trip() {
+        let indices = [U16(3)];
+        let record = StructuredWriteRecord {
+            attribute_id: 0x0011,
+            selector: Selector {
+                indicator: 0x01,
+                indices: Records::Slice(&indices),
+            },
+            value: ZclValue::U8(U8(7)),
+        };
+        let records = [record];
+        let command = WriteAttributesStructured {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 8];
+        assert_eq!(command.to_bytes(&mut data), Ok(7));
+        assert_eq!(data[..7], [0x11, 0x00, 0x01, 0x03, 0x00, 0x20, 0x07]);
+        let decoded = WriteAttributesStructured::from_bytes(&data[..7]).unwrap();
+        let decoded = decoded.records.iter().next().unwrap().unwrap();
+        assert_eq!(decoded.attribute_id, 0x0011);
+        assert!(same_selector(&decoded.selector, &record.selector));
+        assert_eq!(decoded.value, ZclValue::U8(U8(7)));
+    }
+
+    #[test]
+    fn write_structured_response_round_trip() {
+        let indices = [U16(3)];
+        let selector = Selector {
+            indicator: 0x01,
+            indices: Records::Slice(&indices),
+        };
+        let records = [
+            StructuredWriteStatusRecord {
+                status: Status::Success,
+                attribute_id: 0x0010,
+                selector,
+            },
+            StructuredWriteStatusRecord {
+                status: Status::InvalidSelector,
+                attribute_id: 0x0011,
+                selector,
+            },
+        ];
+        let response = WriteAttributesStructuredResponse {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 8];
+        assert_eq!(response.to_bytes(&mut data), Ok(6));
+        assert_eq!(data[..6], [0x8e, 0x11, 0x00, 0x01, 0x03, 0x00]);
+        let decoded = WriteAttributesStructuredResponse::from_bytes(&data[..6]).unwrap();
+        let decoded = decoded.records.iter().next().unwrap().unwrap();
+        assert_eq!(decoded.status, Status::InvalidSelector);
+        assert!(same_selector(&decoded.selector, &selector));
+
+        let response = WriteAttributesStructuredResponse {
+            records: Records::Slice(&records[..1]),
+        };
+        assert_eq!(response.to_bytes(&mut data), Ok(1));
+        assert_eq!(data[0], 0x00);
+    }
+
+    #[test]
+    fn truncated() {
+        // The selector announces two indices but only contains one
+        let command =
+            ReadAttributesStructured::from_bytes(&[0x10, 0x00, 0x02, 0x02, 0x00]).unwrap();
+        let mut records = command.records.iter();
+        assert_eq!(records.next(), Some(Err(ZclError::Serialization)));
+        assert_eq!(records.next(), None);
+        assert_eq!(Selector::from_bytes(&[]), Err(ZclError::Serialization));
+    }
+}
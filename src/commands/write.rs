@@ -0,0 +1,208 @@
+use super::{Record, Records, StatusRecord};
+use crate::types::codec::{Reader, Writer};
+use crate::types::{Attribute, ZclCompatibleType, ZclDecode, ZclEncode, ZclType, ZclValue};
+use crate::{Status, ZclError};
+
+/// An attribute together with its value, as written by the write commands
+/// and sent in [`ReportAttributes`](super::ReportAttributes).
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct AttributeRecord<'a> {
+    pub attribute_id: u16,
+    pub value: ZclValue<'a>,
+}
+
+/// A record of the write commands, see [`AttributeRecord::new`].
+pub type WriteRecord<'a> = AttributeRecord<'a>;
+
+impl<'a> AttributeRecord<'a> {
+    /// A record writing `value` to `attribute`, e.g.
+    /// `WriteRecord::new(&basic::LOCATION_DESCRIPTION, CharacterString(Some("Kitchen")))`.
+    /// Fails with [`Status::ReadOnly`] if the attribute is not writable.
+    pub fn new<T>(attribute: &Attribute<'_, T>, value: T) -> Result<Self, Status>
+    where
+        T: ZclType + Into<ZclValue<'a>>,
+    {
+        if !attribute.writable.is_writable() {
+            return Err(Status::ReadOnly);
+        }
+        Ok(Self {
+            attribute_id: attribute.code,
+            value: value.into(),
+        })
+    }
+}
+
+impl ZclCompatibleType for AttributeRecord<'_> {
+    fn len(&self) -> usize {
+        3 + self.value.len()
+    }
+}
+
+impl ZclEncode for AttributeRecord<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u16(self.attribute_id)?;
+        writer.u8(self.value.type_id())?;
+        writer.value(&self.value)?;
+        Ok(writer.finish())
+    }
+}
+
+impl<'a> ZclDecode<'a> for AttributeRecord<'a> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        let attribute_id = reader.u16()?;
+        let type_id = reader.u8()?;
+        Ok(Self {
+            attribute_id,
+            value: reader.zcl_value(type_id)?,
+        })
+    }
+}
+
+impl<'a> Record<'a> for AttributeRecord<'a> {
+    fn encoded_len(data: &'a [u8]) -> Result<usize, ZclError> {
+        let mut reader = Reader::new(data);
+        reader.u16()?;
+        let type_id = reader.u8()?;
+        reader.skip_value(type_id)?;
+        Ok(reader.finish())
+    }
+}
+
+records_command! {
+    /// Write Attributes (0x02)
+    WriteAttributes { records: AttributeRecord<'a> }
+}
+
+records_command! {
+    /// Write Attributes Undivided (0x03)
+    WriteAttributesUndivided { records: AttributeRecord<'a> }
+}
+
+records_command! {
+    /// Write Attributes No Response (0x05)
+    WriteAttributesNoResponse { records: AttributeRecord<'a> }
+}
+
+/// An attribute which could not be written, see [`WriteAttributesResponse`].
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct WriteAttributeStatusRecord {
+    pub status: Status,
+    pub attribute_id: u16,
+}
+
+impl ZclCompatibleType for WriteAttributeStatusRecord {
+    fn len(&self) -> usize {
+        3
+    }
+}
+
+impl ZclEncode for WriteAttributeStatusRecord {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let mut writer = Writer::new(data);
+        writer.u8(self.status.into())?;
+        writer.u16(self.attribute_id)?;
+        Ok(writer.finish())
+    }
+}
+
+impl ZclDecode<'_> for WriteAttributeStatusRecord {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let mut reader = Reader::new(data);
+        Ok(Self {
+            status: Status::from(reader.u8()?),
+            attribute_id: reader.u16()?,
+        })
+    }
+}
+
+impl Record<'_> for WriteAttributeStatusRecord {}
+
+impl StatusRecord for WriteAttributeStatusRecord {
+    fn status(&self) -> Status {
+        self.status
+    }
+}
+
+records_command! {
+    /// Write Attributes Response (0x04)
+    ///
+    /// Only attributes which could not be written are sent, records with a success
+    /// status are left out. If all writes succeeded, a single success status is
+    /// sent and the decoded records are empty.
+    compact WriteAttributesResponse { records: WriteAttributeStatusRecord }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::general::basic;
+    use crate::types::{CharacterString, U8};
+
+    #[test]
+    fn write_record_from_attribute() {
+        let record = WriteRecord::new(
+            &basic::LOCATION_DESCRIPTION,
+            CharacterString(Some("Kitchen")),
+        )
+        .unwrap();
+        assert_eq!(record.attribute_id, 0x0010);
+        let records = [record];
+        let command = WriteAttributes {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 16];
+        assert_eq!(command.to_bytes(&mut data), Ok(11));
+        assert_eq!(data[..11], *b"\x10\x00\x42\x07Kitchen");
+        let decoded = WriteAttributes::from_bytes(&data[..11]).unwrap();
+        assert_eq!(decoded.records.iter().next(), Some(Ok(record)));
+    }
+
+    #[test]
+    fn write_record_refuses_read_only_attributes() {
+        assert_eq!(
+            WriteRecord::new(&basic::ZCL_VERSION, U8(8)),
+            Err(Status::ReadOnly)
+        );
+    }
+
+    #[test]
+    fn response_with_only_successes_is_compact() {
+        let success = |attribute_id| WriteAttributeStatusRecord {
+            status: Status::Success,
+            attribute_id,
+        };
+        let records = [success(0x0010), success(0x0011)];
+        let response = WriteAttributesResponse {
+            records: Records::Slice(&records),
+        };
+        let mut data = [0u8; 8];
+        assert_eq!(response.len(), 1);
+        assert_eq!(response.to_bytes(&mut data), Ok(1));
+        assert_eq!(data[0], 0x00);
+        let decoded = WriteAttributesResponse::from_bytes(&data[..1]).unwrap();
+        assert_eq!(decoded.records.iter().next(), None);
+
+        // Only the failures are sent if some writes failed
+        let failure = WriteAttributeStatusRecord {
+            status: Status::ReadOnly,
+            attribute_id: 0x0000,
+        };
+        let records = [success(0x0010), failure];
+        let response = WriteAttributesResponse {
+            records: Records::Slice(&records),
+        };
+        assert_eq!(response.len(), 3);
+        assert_eq!(response.to_bytes(&mut data), Ok(3));
+        assert_eq!(data[..3], [0x88, 0x00, 0x00]);
+        let decoded = WriteAttributesResponse::from_bytes(&data[..3]).unwrap();
+        let mut records = decoded.records.iter();
+        assert_eq!(records.next(), Some(Ok(failure)));
+        assert_eq!(records.next(), None);
+        // Received records are encoded as they are
+        let mut encoded = [0u8; 8];
+        assert_eq!(decoded.to_bytes(&mut encoded), Ok(3));
+        assert_eq!(encoded[..3], data[..3]);
+    }
+}
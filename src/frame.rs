@@ -0,0 +1,179 @@
+use crate::ZclError;
+use crate::types::codec;
+use crate::types::{ZclCompatibleType, ZclDecode, ZclEncode};
+
+const FRAME_TYPE_MASK: u8 = 0b0000_0011;
+const MANUFACTURER_SPECIFIC: u8 = 0b0000_0100;
+const DIRECTION: u8 = 0b0000_1000;
+const DISABLE_DEFAULT_RESPONSE: u8 = 0b0001_0000;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum FrameType {
+    /// The command acts across the entire profile, see [`crate::Command`]
+    Global,
+    /// The command is specific to the cluster
+    ClusterSpecific,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+/// The header preceding every ZCL command in the APS payload.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct FrameHeader {
+    pub frame_type: FrameType,
+    /// Set for manufacturer specific commands, which are encoded with the
+    /// manufacturer specific flag in the frame control field.
+    pub manufacturer_code: Option<u16>,
+    pub direction: Direction,
+    pub disable_default_response: bool,
+    pub sequence_number: u8,
+    pub command_id: u8,
+}
+
+impl FrameHeader {
+    /// Splits a ZCL frame into its header and the command payload following it.
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), ZclError> {
+        let header = Self::from_bytes(data)?;
+        Ok((header, &data[header.len()..]))
+    }
+
+    fn frame_control(&self) -> u8 {
+        let mut frame_control = match self.frame_type {
+            FrameType::Global => 0b00,
+            FrameType::ClusterSpecific => 0b01,
+        };
+        if self.manufacturer_code.is_some() {
+            frame_control |= MANUFACTURER_SPECIFIC;
+        }
+        if self.direction == Direction::ServerToClient {
+            frame_control |= DIRECTION;
+        }
+        if self.disable_default_response {
+            frame_control |= DISABLE_DEFAULT_RESPONSE;
+        }
+        frame_control
+    }
+}
+
+impl ZclCompatibleType for FrameHeader {
+    fn len(&self) -> usize {
+        match self.manufacturer_code {
+            Some(_) => 5,
+            None => 3,
+        }
+    }
+}
+
+impl ZclEncode for FrameHeader {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        if data.len() < self.len() {
+            return Err(ZclError::Serialization);
+        }
+        let mut offset = codec::write_uint(data, self.frame_control() as u64, 1)?;
+        if let Some(code) = self.manufacturer_code {
+            offset += codec::write_uint(&mut data[offset..], code as u64, 2)?;
+        }
+        offset += codec::write_uint(&mut data[offset..], self.sequence_number as u64, 1)?;
+        offset += codec::write_uint(&mut data[offset..], self.command_id as u64, 1)?;
+        Ok(offset)
+    }
+}
+
+impl ZclDecode<'_> for FrameHeader {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let frame_control = codec::read_uint(data, 1)? as u8;
+        let frame_type = match frame_control & FRAME_TYPE_MASK {
+            0b00 => FrameType::Global,
+            0b01 => FrameType::ClusterSpecific,
+            _ => return Err(ZclError::ValueOutOfRange),
+        };
+        let mut offset = 1;
+        let manufacturer_code = if frame_control & MANUFACTURER_SPECIFIC != 0 {
+            offset += 2;
+            Some(codec::read_uint(&data[1..], 2)? as u16)
+        } else {
+            None
+        };
+        let [sequence_number, command_id] =
+            codec::read_bytes(&data[offset..], 2)?.try_into().unwrap();
+        Ok(Self {
+            frame_type,
+            manufacturer_code,
+            direction: if frame_control & DIRECTION != 0 {
+                Direction::ServerToClient
+            } else {
+                Direction::ClientToServer
+            },
+            disable_default_response: frame_control & DISABLE_DEFAULT_RESPONSE != 0,
+            sequence_number,
+            command_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_header() {
+        let data = [0x18, 0x2a, 0x01, 0xaa];
+        let (header, payload) = FrameHeader::parse(&data).unwrap();
+        assert_eq!(
+            header,
+            FrameHeader {
+                frame_type: FrameType::Global,
+                manufacturer_code: None,
+                direction: Direction::ServerToClient,
+                disable_default_response: true,
+                sequence_number: 0x2a,
+                command_id: 0x01,
+            }
+        );
+        assert_eq!(payload, [0xaa]);
+        let mut buf = [0u8; 3];
+        assert_eq!(header.to_bytes(&mut buf), Ok(3));
+        assert_eq!(buf, data[..3]);
+    }
+
+    #[test]
+    fn manufacturer_specific_header() {
+        let header = FrameHeader {
+            frame_type: FrameType::ClusterSpecific,
+            manufacturer_code: Some(0x115f),
+            direction: Direction::ClientToServer,
+            disable_default_response: false,
+            sequence_number: 0x07,
+            command_id: 0x02,
+        };
+        let mut buf = [0u8; 5];
+        assert_eq!(header.len(), 5);
+        assert_eq!(header.to_bytes(&mut buf), Ok(5));
+        assert_eq!(buf, [0x05, 0x5f, 0x11, 0x07, 0x02]);
+        assert_eq!(FrameHeader::parse(&buf), Ok((header, &[][..])));
+    }
+
+    #[test]
+    fn invalid_headers() {
+        assert_eq!(FrameHeader::from_bytes(&[]), Err(ZclError::Serialization));
+        assert_eq!(
+            FrameHeader::from_bytes(&[0x00, 0x01]),
+            Err(ZclError::Serialization)
+        );
+        // Manufacturer specific flag without room for the code
+        assert_eq!(
+            FrameHeader::from_bytes(&[0x04, 0x5f, 0x11]),
+            Err(ZclError::Serialization)
+        );
+        assert_eq!(
+            FrameHeader::from_bytes(&[0x02, 0x01, 0x00]),
+            Err(ZclError::ValueOutOfRange)
+        );
+        let header = FrameHeader::from_bytes(&[0x00, 0x01, 0x00]).unwrap();
+        assert_eq!(header.to_bytes(&mut [0u8; 2]), Err(ZclError::Serialization));
+    }
+}
@@ -1,6 +1,12 @@
 #![no_std]
 use thiserror::Error;
 
+// The generated definitions refer to the crate as `::zcl`, like those of downstream crates
+extern crate self as zcl;
+
+pub mod clusters;
+pub mod commands;
+pub mod frame;
 pub mod types;
 
 include!(concat!(env!("OUT_DIR"), "/generated.rs"));
@@ -11,15 +17,89 @@ pub enum ZclError {
     Serialization,
     #[error("value is out of range")]
     ValueOutOfRange,
+    #[error("string is not valid UTF-8")]
+    InvalidUtf8,
+    #[error("unknown data type 0x{0:02x}")]
+    UnknownDataType(u8),
+    #[error("value is of a different data type")]
+    TypeMismatch,
+    #[error("unknown command 0x{0:02x}")]
+    UnknownCommand(u8),
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+/// The global commands which act across the entire profile. Their payloads are
+/// defined in [`commands`].
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 #[repr(u8)]
 pub enum Command {
-    PlaceholderTodo,
+    ReadAttributes = 0x00,
+    ReadAttributesResponse = 0x01,
+    WriteAttributes = 0x02,
+    WriteAttributesUndivided = 0x03,
+    WriteAttributesResponse = 0x04,
+    WriteAttributesNoResponse = 0x05,
+    ConfigureReporting = 0x06,
+    ConfigureReportingResponse = 0x07,
+    ReadReportingConfiguration = 0x08,
+    ReadReportingConfigurationResponse = 0x09,
+    ReportAttributes = 0x0a,
+    DefaultResponse = 0x0b,
+    DiscoverAttributes = 0x0c,
+    DiscoverAttributesResponse = 0x0d,
+    ReadAttributesStructured = 0x0e,
+    WriteAttributesStructured = 0x0f,
+    WriteAttributesStructuredResponse = 0x10,
+    DiscoverCommandsReceived = 0x11,
+    DiscoverCommandsReceivedResponse = 0x12,
+    DiscoverCommandsGenerated = 0x13,
+    DiscoverCommandsGeneratedResponse = 0x14,
+    DiscoverAttributesExtended = 0x15,
+    DiscoverAttributesExtendedResponse = 0x16,
+}
+
+impl TryFrom<u8> for Command {
+    type Error = ZclError;
+
+    fn try_from(value: u8) -> Result<Self, ZclError> {
+        Ok(match value {
+            0x00 => Self::ReadAttributes,
+            0x01 => Self::ReadAttributesResponse,
+            0x02 => Self::WriteAttributes,
+            0x03 => Self::WriteAttributesUndivided,
+            0x04 => Self::WriteAttributesResponse,
+            0x05 => Self::WriteAttributesNoResponse,
+            0x06 => Self::ConfigureReporting,
+            0x07 => Self::ConfigureReportingResponse,
+            0x08 => Self::ReadReportingConfiguration,
+            0x09 => Self::ReadReportingConfigurationResponse,
+            0x0a => Self::ReportAttributes,
+            0x0b => Self::DefaultResponse,
+            0x0c => Self::DiscoverAttributes,
+            0x0d => Self::DiscoverAttributesResponse,
+            0x0e => Self::ReadAttributesStructured,
+            0x0f => Self::WriteAttributesStructured,
+            0x10 => Self::WriteAttributesStructuredResponse,
+            0x11 => Self::DiscoverCommandsReceived,
+            0x12 => Self::DiscoverCommandsReceivedResponse,
+            0x13 => Self::DiscoverCommandsGenerated,
+            0x14 => Self::DiscoverCommandsGeneratedResponse,
+            0x15 => Self::DiscoverAttributesExtended,
+            0x16 => Self::DiscoverAttributesExtendedResponse,
+            _ => return Err(ZclError::UnknownCommand(value)),
+        })
+    }
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+impl From<Command> for u8 {
+    fn from(value: Command) -> Self {
+        value as u8
+    }
+}
+
+/// The status codes of the ZCL. Codes which are deprecated by the current
+/// specification are still decoded, [`Status::canonical`] maps them to their
+/// replacement.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 #[repr(u8)]
 pub enum Status {
     /// Operation was successful
@@ -28,12 +108,24 @@ pub enum Status {
     Failure = 0x01,
     /// The sender of the command does not have authorization to carry out this command
     NotAuthorized = 0x7e,
-    /// Unknown purpose
-    Reserved7f = 0x7f,
+    /// A reserved field, subfield or bit contains a non-zero value
+    ReservedFieldNotZero = 0x7f,
     /// The command appears to contain the wrong fields, as detected either by the presence of one or more invalid field entries or by there being missing fields. Command not carried out. Implementer has discretion as to whether to return this error or INVALID_FIELD
     MalformedCommand = 0x80,
     /// The specified command is not supported on the device. Command not carried out
     UnsupCommand = 0x81,
+    /// The specified general command is not supported on the device
+    ///
+    /// Deprecated, use [`Status::UnsupCommand`] instead
+    UnsupGeneralCommand = 0x82,
+    /// A manufacturer specific unicast, cluster specific command was received with an unknown manufacturer code, or the manufacturer code was recognized but the command is not supported
+    ///
+    /// Deprecated, use [`Status::UnsupCommand`] instead
+    UnsupManufClusterCommand = 0x83,
+    /// A manufacturer specific unicast, ZCL specific command was received with an unknown manufacturer code, or the manufacturer code was recognized but the command is not supported
+    ///
+    /// Deprecated, use [`Status::UnsupCommand`] instead
+    UnsupManufGeneralCommand = 0x84,
     /// At least one field of the command contains an incorrect value, according to the specification the device is implemented to
     InvalidField = 0x85,
     /// The specified attribute does not exist on the device
@@ -44,6 +136,10 @@ pub enum Status {
     ReadOnly = 0x88,
     /// An operation failed due to an insufficient amount of free space available
     InsufficientSpace = 0x89,
+    /// An attempt to create an entry in a table failed due to a duplicate entry already being present in the table
+    ///
+    /// Deprecated, use [`Status::Success`] instead
+    DuplicateExists = 0x8a,
     /// The requested information (e.g., table entry) could not be found
     NotFound = 0x8b,
     /// Periodic reports cannot be issued for this attribute
@@ -52,8 +148,26 @@ pub enum Status {
     InvalidDataType = 0x8d,
     /// The selector for an attribute is incorrect
     InvalidSelector = 0x8e,
+    /// A request has been made to read an attribute that the requestor is not authorized to read
+    ///
+    /// Deprecated, use [`Status::NotAuthorized`] instead
+    WriteOnly = 0x8f,
+    /// Setting the requested values would put the device in an inconsistent state on startup
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    InconsistentStartupState = 0x90,
+    /// An attempt has been made to write an attribute that is present but is defined using an out-of-band method and not over the air
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    DefinedOutOfBand = 0x91,
     /// The supplied values (e.g., contents of table cells) are inconsistent
-    Reserved92 = 0x92,
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    Inconsistent = 0x92,
+    /// The credentials presented by the device sending the command are not sufficient to perform this action
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    ActionDenied = 0x93,
     /// The exchange was aborted due to excessive response time
     Timeout = 0x94,
     /// Failed case when a client or a server decides to abort the upgrade process
@@ -68,16 +182,330 @@ pub enum Status {
     RequireMoreImage = 0x99,
     /// The command has been received and is being processed
     NotificationPending = 0x9a,
+    /// An operation was unsuccessful due to a hardware failure
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    HardwareFailure = 0xc0,
+    /// An operation was unsuccessful due to a software failure
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    SoftwareFailure = 0xc1,
     /// An error occurred during calibration
-    ReservedC2 = 0xc2,
+    ///
+    /// Deprecated, use [`Status::Failure`] instead
+    CalibrationError = 0xc2,
     /// The cluster is not supported
     UnsupportedCluster = 0xc3,
-    /// Something else
+    /// Limit of attribute range reached. Value is trimmed to closest limit
+    ///
+    /// Deprecated, use [`Status::Success`] instead
+    LimitReached = 0xc4,
+    /// A status code which is not defined by the specification
     Invalid(u8),
 }
 
+impl Status {
+    /// Maps deprecated status codes to the code the specification demands in their place.
+    pub fn canonical(self) -> Self {
+        match self {
+            Self::DuplicateExists | Self::LimitReached => Self::Success,
+            Self::InconsistentStartupState
+            | Self::DefinedOutOfBand
+            | Self::Inconsistent
+            | Self::ActionDenied
+            | Self::HardwareFailure
+            | Self::SoftwareFailure
+            | Self::CalibrationError => Self::Failure,
+            Self::WriteOnly => Self::NotAuthorized,
+            Self::UnsupGeneralCommand
+            | Self::UnsupManufClusterCommand
+            | Self::UnsupManufGeneralCommand => Self::UnsupCommand,
+            other => other,
+        }
+    }
+}
+
+impl From<u8> for Status {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => Self::Success,
+            0x01 => Self::Failure,
+            0x7e => Self::NotAuthorized,
+            0x7f => Self::ReservedFieldNotZero,
+            0x80 => Self::MalformedCommand,
+            0x81 => Self::UnsupCommand,
+            0x82 => Self::UnsupGeneralCommand,
+            0x83 => Self::UnsupManufClusterCommand,
+            0x84 => Self::UnsupManufGeneralCommand,
+            0x85 => Self::InvalidField,
+            0x86 => Self::UnsupportedAttribute,
+            0x87 => Self::InvalidValue,
+            0x88 => Self::ReadOnly,
+            0x89 => Self::InsufficientSpace,
+            0x8a => Self::DuplicateExists,
+            0x8b => Self::NotFound,
+            0x8c => Self::UnreportableAttribute,
+            0x8d => Self::InvalidDataType,
+            0x8e => Self::InvalidSelector,
+            0x8f => Self::WriteOnly,
+            0x90 => Self::InconsistentStartupState,
+            0x91 => Self::DefinedOutOfBand,
+            0x92 => Self::Inconsistent,
+            0x93 => Self::ActionDenied,
+            0x94 => Self::Timeout,
+            0x95 => Self::Abort,
+            0x96 => Self::InvalidImage,
+            0x97 => Self::WaitForData,
+            0x98 => Self::NoImageAvailable,
+            0x99 => Self::RequireMoreImage,
+            0x9a => Self::NotificationPending,
+            0xc0 => Self::HardwareFailure,
+            0xc1 => Self::SoftwareFailure,
+            0xc2 => Self::CalibrationError,
+            0xc3 => Self::UnsupportedCluster,
+            0xc4 => Self::LimitReached,
+            other => Self::Invalid(other),
+        }
+    }
+}
+
+impl From<Status> for u8 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Success => 0x00,
+            Status::Failure => 0x01,
+            Status::NotAuthorized => 0x7e,
+            Status::ReservedFieldNotZero => 0x7f,
+            Status::MalformedCommand => 0x80,
+            Status::UnsupCommand => 0x81,
+            Status::UnsupGeneralCommand => 0x82,
+            Status::UnsupManufClusterCommand => 0x83,
+            Status::UnsupManufGeneralCommand => 0x84,
+            Status::InvalidField => 0x85,
+            Status::UnsupportedAttribute => 0x86,
+            Status::InvalidValue => 0x87,
+            Status::ReadOnly => 0x88,
+            Status::InsufficientSpace => 0x89,
+            Status::DuplicateExists => 0x8a,
+            Status::NotFound => 0x8b,
+            Status::UnreportableAttribute => 0x8c,
+            Status::InvalidDataType => 0x8d,
+            Status::InvalidSelector => 0x8e,
+            Status::WriteOnly => 0x8f,
+            Status::InconsistentStartupState => 0x90,
+            Status::DefinedOutOfBand => 0x91,
+            Status::Inconsistent => 0x92,
+            Status::ActionDenied => 0x93,
+            Status::Timeout => 0x94,
+            Status::Abort => 0x95,
+            Status::InvalidImage => 0x96,
+            Status::WaitForData => 0x97,
+            Status::NoImageAvailable => 0x98,
+            Status::RequireMoreImage => 0x99,
+            Status::NotificationPending => 0x9a,
+            Status::HardwareFailure => 0xc0,
+            Status::SoftwareFailure => 0xc1,
+            Status::CalibrationError => 0xc2,
+            Status::UnsupportedCluster => 0xc3,
+            Status::LimitReached => 0xc4,
+            Status::Invalid(other) => other,
+        }
+    }
+}
+
 pub struct Cluster<'a, Ts> {
     pub code: u16,
     pub name: &'a str,
+    pub attributes: &'a [types::AttributeDescriptor<'a>],
     pub meta: Ts,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::Direction;
+    use crate::general::level::{Command, MoveToLevel};
+    use crate::general::{BatteryAlarmState, StartUpOnOff, identify, on_off};
+    use crate::types::*;
+    use crate::{Status, ZclError};
+
+    #[test]
+    fn commands_sharing_an_id_are_told_apart_by_direction() {
+        let data = [0x0a, 0x00];
+        let received = identify::Command::decode(Direction::ClientToServer, 0x00, &data);
+        assert_eq!(
+            received,
+            Ok(identify::Command::Identify(identify::Identify {
+                identify_time: U16(10)
+            }))
+        );
+        let generated = identify::Command::decode(Direction::ServerToClient, 0x00, &data);
+        assert_eq!(
+            generated,
+            Ok(identify::Command::IdentifyQueryResponse(
+                identify::IdentifyQueryResponse { timeout: U16(10) }
+            ))
+        );
+        let generated = generated.unwrap();
+        assert_eq!(generated.id(), 0x00);
+        assert_eq!(generated.direction(), Direction::ServerToClient);
+        // IdentifyQuery is only received by the server
+        assert_eq!(
+            identify::Command::decode(Direction::ServerToClient, 0x01, &[]),
+            Err(ZclError::UnknownCommand(0x01))
+        );
+    }
+
+    #[test]
+    fn command_dispatch_round_trip() {
+        let command = on_off::Command::from(on_off::OffWithEffect {
+            effect_identifier: Enum8(on_off::EffectIdentifier::DyingLight),
+            effect_variant: Enum8(on_off::EffectVariant::Default),
+        });
+        assert_eq!(command.id(), 0x40);
+        assert_eq!(command.direction(), Direction::ClientToServer);
+        let mut data = [0u8; 4];
+        assert_eq!(command.len(), 2);
+        assert_eq!(command.to_bytes(&mut data), Ok(2));
+        assert_eq!(data[..2], [0x01, 0x00]);
+        assert_eq!(
+            on_off::Command::decode(Direction::ClientToServer, 0x40, &data[..2]),
+            Ok(command)
+        );
+        // Unknown effects are rejected
+        assert_eq!(
+            on_off::Command::decode(Direction::ClientToServer, 0x40, &[0x02, 0x00]),
+            Err(ZclError::ValueOutOfRange)
+        );
+        assert_eq!(
+            on_off::Command::decode(Direction::ClientToServer, 0x02, &[]),
+            Ok(on_off::Command::Toggle(on_off::Toggle {}))
+        );
+    }
+
+    #[test]
+    fn missing_options_decode_as_zero() {
+        // Devices implementing earlier revisions omit OptionsMask and OptionsOverride
+        let Ok(Command::MoveToLevel(command)) =
+            Command::decode(Direction::ClientToServer, 0x00, &[0x80, 0x0a, 0x00])
+        else {
+            panic!("MoveToLevel without options is rejected");
+        };
+        assert_eq!(command.level.0, 0x80);
+        assert_eq!(command.transition_time.0, 10);
+        assert_eq!(command.options_mask.0.into_raw(), 0);
+        assert_eq!(command.options_override.0.into_raw(), 0);
+        // Both options are encoded and read back
+        let data = [0x80, 0x0a, 0x00, 0x01, 0x01];
+        let command = MoveToLevel::from_bytes(&data).unwrap();
+        assert_eq!(command.options_mask.0.into_raw(), 1);
+        assert_eq!(command.options_override.0.into_raw(), 1);
+        let mut buf = [0u8; 5];
+        assert_eq!(command.to_bytes(&mut buf), Ok(5));
+        assert_eq!(buf, data);
+        // A truncated required field is still an error
+        assert!(Command::decode(Direction::ClientToServer, 0x00, &[0x80, 0x0a]).is_err());
+    }
+
+    #[test]
+    fn bitmap_flags() {
+        let mut state = BatteryAlarmState::SOURCE_1_MIN_THRESHOLD;
+        state.insert(BatteryAlarmState::MAINS_POWER_SUPPLY_LOST);
+        assert_eq!(state.bits(), 0x4000_0001);
+        assert!(state.contains(BatteryAlarmState::SOURCE_1_MIN_THRESHOLD));
+        assert!(state.contains(
+            BatteryAlarmState::SOURCE_1_MIN_THRESHOLD | BatteryAlarmState::MAINS_POWER_SUPPLY_LOST
+        ));
+        assert!(!state.contains(BatteryAlarmState::SOURCE_2_MIN_THRESHOLD));
+        assert!(state.contains(BatteryAlarmState::EMPTY));
+        state.remove(BatteryAlarmState::SOURCE_1_MIN_THRESHOLD);
+        assert_eq!(state, BatteryAlarmState::MAINS_POWER_SUPPLY_LOST);
+        // Removing a bit which is not set changes nothing
+        state.remove(BatteryAlarmState::SOURCE_3_THRESHOLD_3);
+        assert_eq!(state, BatteryAlarmState::MAINS_POWER_SUPPLY_LOST);
+
+        // The reserved bits between the groups and bit 31 are cleared
+        assert_eq!(BatteryAlarmState::ALL.bits(), 0x40f0_3c0f);
+        assert_eq!(
+            BatteryAlarmState::from_bits(u32::MAX),
+            BatteryAlarmState::ALL
+        );
+        assert_eq!(
+            BatteryAlarmState::from_raw(0x8000_0010),
+            BatteryAlarmState::EMPTY
+        );
+        let value = Bitmap32::<BatteryAlarmState>::from_bytes(&[0x01, 0xfc, 0x00, 0xc0]).unwrap();
+        assert_eq!(value.0.into_raw(), 0x4000_3c01);
+    }
+
+    #[test]
+    fn enum_conversions() {
+        assert_eq!(StartUpOnOff::try_from(0x02), Ok(StartUpOnOff::Toggle));
+        assert_eq!(u8::from(StartUpOnOff::Toggle), 0x02);
+        assert_eq!(StartUpOnOff::NON_VALUE, StartUpOnOff::PreviousValue);
+        assert_eq!(StartUpOnOff::try_from(0x03), Err(ZclError::ValueOutOfRange));
+        assert_eq!(StartUpOnOff::try_from_raw(0x80), None);
+        assert_eq!(
+            Enum8::<StartUpOnOff>::from_bytes(&[0x03]),
+            Err(ZclError::ValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn lossless_enum_round_trip() {
+        // Known values decode to their variant
+        let known = Enum8::<Lossless<StartUpOnOff>>::from_bytes(&[0x01]).unwrap();
+        assert_eq!(known.0, Lossless::Known(StartUpOnOff::On));
+        assert_eq!(known.0.known(), Some(StartUpOnOff::On));
+        assert!(!known.is_non_value());
+
+        // Unknown values are kept and written back unchanged
+        let unknown = Enum8::<Lossless<StartUpOnOff>>::from_bytes(&[0x42]).unwrap();
+        assert_eq!(unknown.0, Lossless::Unknown(0x42));
+        assert_eq!(unknown.0.known(), None);
+        let mut buf = [0u8; 1];
+        assert_eq!(unknown.to_bytes(&mut buf), Ok(1));
+        assert_eq!(buf, [0x42]);
+
+        // Every raw value survives the round trip
+        for raw in 0..=u8::MAX {
+            let value = Lossless::<StartUpOnOff>::try_from_raw(raw).unwrap();
+            assert_eq!(value.into_raw(), raw);
+        }
+        let non_value = Enum8::<Lossless<StartUpOnOff>>::from_bytes(&[0xff]).unwrap();
+        assert!(non_value.is_non_value());
+    }
+
+    #[test]
+    fn status_round_trip() {
+        let mut defined = 0;
+        for code in 0..=u8::MAX {
+            let status = Status::from(code);
+            assert_eq!(u8::from(status), code);
+            if status != Status::Invalid(code) {
+                defined += 1;
+            }
+        }
+        assert_eq!(defined, 36);
+        assert_eq!(Status::from(0x8a), Status::DuplicateExists);
+        assert_eq!(Status::from(0xc4), Status::LimitReached);
+        // Codes which are not defined are kept
+        assert_eq!(Status::from(0x02), Status::Invalid(0x02));
+        assert_eq!(u8::from(Status::Invalid(0xfe)), 0xfe);
+    }
+
+    #[test]
+    fn canonical_status() {
+        assert_eq!(Status::DuplicateExists.canonical(), Status::Success);
+        assert_eq!(Status::LimitReached.canonical(), Status::Success);
+        assert_eq!(Status::HardwareFailure.canonical(), Status::Failure);
+        assert_eq!(Status::CalibrationError.canonical(), Status::Failure);
+        assert_eq!(Status::WriteOnly.canonical(), Status::NotAuthorized);
+        assert_eq!(
+            Status::UnsupManufGeneralCommand.canonical(),
+            Status::UnsupCommand
+        );
+        // Current and unknown codes are unchanged
+        assert_eq!(Status::ReadOnly.canonical(), Status::ReadOnly);
+        assert_eq!(Status::Invalid(0x02).canonical(), Status::Invalid(0x02));
+    }
+}
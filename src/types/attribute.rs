@@ -1,7 +1,10 @@
-use crate::types::ZclType;
+use core::cmp::Ordering;
+
+use crate::types::{ZclType, ZclValue};
+use crate::{Status, ZclError};
 
 #[derive(PartialEq, Debug, Copy, Clone)]
-pub enum ValueOrAttributeReference<T: ZclType> {
+pub enum ValueOrAttributeReference<T> {
     /// The value of another attribute on the same cluster
     AttributeReference(u16),
     /// An immediate value
@@ -9,7 +12,7 @@ pub enum ValueOrAttributeReference<T: ZclType> {
 }
 
 #[derive(PartialEq, Debug, Copy, Clone)]
-pub enum AttributeRange<T: ZclType> {
+pub enum AttributeRange<T> {
     // Any value but explicitly excluding the the NON_VALUE
     Value,
     // Any value. If a NON_VALUE is defined for the data type, it explicitly is interpreted as a
@@ -30,16 +33,258 @@ pub enum AttributeSide {
     Either,
 }
 
+impl AttributeSide {
+    /// Whether an attribute of this side is implemented by `side`
+    pub fn applies_to(self, side: AttributeSide) -> bool {
+        self == side || self == Self::Either || side == Self::Either
+    }
+}
+
+/// Whether an attribute can be written, given by the `W` and `*W` access flags
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum WriteAccess {
+    /// The attribute is read only
+    No,
+    /// Every implementation supports writing the attribute
+    Mandatory,
+    /// Implementations may choose to support writing the attribute or keep it read only
+    Optional,
+}
+
+impl WriteAccess {
+    /// Whether writing the attribute may be supported
+    pub fn is_writable(self) -> bool {
+        self != Self::No
+    }
+}
+
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub struct Attribute<'a, T: ZclType> {
     pub code: u16,
     pub name: &'a str,
     pub side: AttributeSide,
     pub readable: bool,
-    pub writable: bool,
+    pub writable: WriteAccess,
     pub reportable: bool,
     pub scene: bool,
     pub mandatory: bool,
+    /// The manufacturer defining the attribute, `None` for standard attributes
+    pub manufacturer_code: Option<u16>,
     pub default: Option<T>,
     pub range: AttributeRange<T>,
 }
+
+/// An [`Attribute`] with its data type erased, so that the attributes of a cluster
+/// can be handled uniformly at runtime.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct AttributeDescriptor<'a> {
+    pub code: u16,
+    pub name: &'a str,
+    /// The ID of the attribute's data type
+    pub type_id: u8,
+    pub side: AttributeSide,
+    pub readable: bool,
+    pub writable: WriteAccess,
+    pub reportable: bool,
+    pub scene: bool,
+    pub mandatory: bool,
+    /// The manufacturer defining the attribute, `None` for standard attributes
+    pub manufacturer_code: Option<u16>,
+    pub default: Option<ZclValue<'a>>,
+    pub range: AttributeRange<ZclValue<'a>>,
+}
+
+impl<T: ZclType> ValueOrAttributeReference<T> {
+    /// Resolves the boundary to a value. References to attributes which are not
+    /// available or hold their non-value yield `None` and impose no limit.
+    fn resolve(self, resolver: &impl Fn(u16) -> Option<T>) -> Option<T> {
+        match self {
+            Self::AttributeReference(code) => resolver(code).filter(|x| !x.is_non_value()),
+            Self::Value(x) => Some(x),
+        }
+    }
+}
+
+impl<T: ZclType + Copy> Attribute<'_, T> {
+    /// Checks whether `value` may be written to this attribute according to its range.
+    /// `resolver` provides the current value of other attributes on the same cluster
+    /// which the range refers to.
+    ///
+    /// A bound referring to an attribute which `resolver` does not provide, or which
+    /// holds its non-value, imposes no limit. Values which cannot be ordered against a
+    /// bound, e.g. NaN or a [`TimeOfDay`](crate::types::TimeOfDay) which has no
+    /// ordering, are rejected.
+    pub fn validate(&self, value: &T, resolver: impl Fn(u16) -> Option<T>) -> Result<(), Status> {
+        let valid = match self.range {
+            AttributeRange::Value => !value.is_non_value(),
+            AttributeRange::Full | AttributeRange::FullWithNone => true,
+            AttributeRange::InclusiveRange(min, max) => {
+                let within = |bound: ValueOrAttributeReference<T>, outside: Ordering| {
+                    bound
+                        .resolve(&resolver)
+                        .is_none_or(|bound| value.compare(&bound).is_some_and(|x| x != outside))
+                };
+                within(min, Ordering::Less) && within(max, Ordering::Greater)
+            }
+            AttributeRange::Size(size) => value.size() <= size,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Status::InvalidValue)
+        }
+    }
+
+    /// Like [`Self::validate`], for a value received with its data type. Fails with
+    /// [`Status::InvalidDataType`] if the data type does not match the attribute.
+    pub fn validate_value<'v>(
+        &self,
+        value: ZclValue<'v>,
+        resolver: impl Fn(u16) -> Option<T>,
+    ) -> Result<(), Status>
+    where
+        T: TryFrom<ZclValue<'v>, Error = ZclError>,
+    {
+        if value.type_id() != T::ID {
+            return Err(Status::InvalidDataType);
+        }
+        let value = T::try_from(value).map_err(|e| match e {
+            ZclError::TypeMismatch => Status::InvalidDataType,
+            _ => Status::InvalidValue,
+        })?;
+        self.validate(&value, resolver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CharacterString, F32, TimeOfDay, U8, U16};
+
+    fn attribute<T: ZclType>(range: AttributeRange<T>) -> Attribute<'static, T> {
+        Attribute {
+            code: 0x0000,
+            name: "Test",
+            side: AttributeSide::Server,
+            readable: true,
+            writable: WriteAccess::Mandatory,
+            reportable: false,
+            scene: false,
+            mandatory: true,
+            manufacturer_code: None,
+            default: None,
+            range,
+        }
+    }
+
+    fn unresolved<T>(_: u16) -> Option<T> {
+        None
+    }
+
+    #[test]
+    fn value_excludes_the_non_value() {
+        let attr = attribute(AttributeRange::<U8>::Value);
+        assert_eq!(attr.validate(&U8(0xfe), unresolved), Ok(()));
+        assert_eq!(
+            attr.validate(&U8(0xff), unresolved),
+            Err(Status::InvalidValue)
+        );
+        for range in [AttributeRange::Full, AttributeRange::FullWithNone] {
+            assert_eq!(attribute(range).validate(&U8(0xff), unresolved), Ok(()));
+        }
+    }
+
+    #[test]
+    fn inclusive_range_with_values() {
+        let attr = attribute(AttributeRange::InclusiveRange(
+            ValueOrAttributeReference::Value(U8(0x01)),
+            ValueOrAttributeReference::Value(U8(0xfe)),
+        ));
+        assert_eq!(
+            attr.validate(&U8(0x00), unresolved),
+            Err(Status::InvalidValue)
+        );
+        assert_eq!(attr.validate(&U8(0x01), unresolved), Ok(()));
+        assert_eq!(attr.validate(&U8(0xfe), unresolved), Ok(()));
+        assert_eq!(
+            attr.validate(&U8(0xff), unresolved),
+            Err(Status::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn inclusive_range_with_references() {
+        let attr = attribute(AttributeRange::InclusiveRange(
+            ValueOrAttributeReference::AttributeReference(0x0002),
+            ValueOrAttributeReference::AttributeReference(0x0003),
+        ));
+        let resolver = |code| match code {
+            0x0002 => Some(U16(10)),
+            0x0003 => Some(U16(20)),
+            _ => None,
+        };
+        assert_eq!(attr.validate(&U16(9), resolver), Err(Status::InvalidValue));
+        assert_eq!(attr.validate(&U16(10), resolver), Ok(()));
+        assert_eq!(attr.validate(&U16(20), resolver), Ok(()));
+        assert_eq!(attr.validate(&U16(21), resolver), Err(Status::InvalidValue));
+        // Missing attributes and non-values impose no limit
+        assert_eq!(attr.validate(&U16(21), unresolved), Ok(()));
+        let resolver = |code| (code == 0x0003).then_some(U16(0xffff));
+        assert_eq!(attr.validate(&U16(0xfffe), resolver), Ok(()));
+    }
+
+    #[test]
+    fn inclusive_range_rejects_unordered_values() {
+        let attr = attribute(AttributeRange::InclusiveRange(
+            ValueOrAttributeReference::Value(F32(0.0)),
+            ValueOrAttributeReference::Value(F32(1.0)),
+        ));
+        assert_eq!(attr.validate(&F32(0.5), unresolved), Ok(()));
+        assert_eq!(
+            attr.validate(&F32(f32::NAN), unresolved),
+            Err(Status::InvalidValue)
+        );
+        let time = TimeOfDay {
+            hours: 12,
+            minutes: 0,
+            seconds: 0,
+            hundredths: 0,
+        };
+        let attr = attribute(AttributeRange::InclusiveRange(
+            ValueOrAttributeReference::Value(time),
+            ValueOrAttributeReference::Value(time),
+        ));
+        assert_eq!(attr.validate(&time, unresolved), Err(Status::InvalidValue));
+    }
+
+    #[test]
+    fn size_limits_strings() {
+        let attr = attribute(AttributeRange::Size(3));
+        assert_eq!(
+            attr.validate(&CharacterString(Some("abc")), unresolved),
+            Ok(())
+        );
+        assert_eq!(
+            attr.validate(&CharacterString(Some("abcd")), unresolved),
+            Err(Status::InvalidValue)
+        );
+        assert_eq!(attr.validate(&CharacterString(None), unresolved), Ok(()));
+    }
+
+    #[test]
+    fn validate_value_checks_the_data_type() {
+        let attr = attribute(AttributeRange::InclusiveRange(
+            ValueOrAttributeReference::Value(U8(1)),
+            ValueOrAttributeReference::Value(U8(10)),
+        ));
+        assert_eq!(attr.validate_value(ZclValue::U8(U8(5)), unresolved), Ok(()));
+        assert_eq!(
+            attr.validate_value(ZclValue::U8(U8(11)), unresolved),
+            Err(Status::InvalidValue)
+        );
+        assert_eq!(
+            attr.validate_value(ZclValue::U16(U16(5)), unresolved),
+            Err(Status::InvalidDataType)
+        );
+    }
+}
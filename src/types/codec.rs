@@ -0,0 +1,410 @@
+use crate::ZclError;
+use crate::types::{ZclCompatibleType, ZclDecode, ZclEncode, ZclValue};
+
+/// Little-endian wire representation of the primitive values wrapped by the
+/// fixed-width ZCL types. The width is passed explicitly because several ZCL
+/// types (e.g. 24, 40, 48 and 56 bit integers) are stored in a wider rust type.
+pub(crate) trait Primitive: Sized {
+    fn write_le(&self, data: &mut [u8], width: usize) -> Result<usize, ZclError>;
+    fn read_le(data: &[u8], width: usize) -> Result<Self, ZclError>;
+    /// Bitwise identity, used to detect non-values. Unlike `==` this matches NaN.
+    fn matches(&self, other: &Self) -> bool;
+}
+
+pub(crate) fn write_bytes(data: &mut [u8], bytes: &[u8]) -> Result<usize, ZclError> {
+    data.get_mut(..bytes.len())
+        .ok_or(ZclError::Serialization)?
+        .copy_from_slice(bytes);
+    Ok(bytes.len())
+}
+
+pub(crate) fn read_bytes(data: &[u8], width: usize) -> Result<&[u8], ZclError> {
+    data.get(..width).ok_or(ZclError::Serialization)
+}
+
+pub(crate) fn write_uint(data: &mut [u8], value: u64, width: usize) -> Result<usize, ZclError> {
+    if width < 8 && value >> (width * 8) != 0 {
+        return Err(ZclError::ValueOutOfRange);
+    }
+    write_bytes(data, &value.to_le_bytes()[..width])
+}
+
+pub(crate) fn read_uint(data: &[u8], width: usize) -> Result<u64, ZclError> {
+    let mut bytes = [0u8; 8];
+    bytes[..width].copy_from_slice(read_bytes(data, width)?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_int(data: &mut [u8], value: i64, width: usize) -> Result<usize, ZclError> {
+    let bits = width as u32 * 8;
+    if bits < 64 && (value < -(1i64 << (bits - 1)) || value >= 1i64 << (bits - 1)) {
+        return Err(ZclError::ValueOutOfRange);
+    }
+    write_bytes(data, &value.to_le_bytes()[..width])
+}
+
+fn read_int(data: &[u8], width: usize) -> Result<i64, ZclError> {
+    // Place the value in the upper bytes so that the arithmetic shift sign-extends it
+    let shift = (8 - width as u32) * 8;
+    Ok(((read_uint(data, width)? << shift) as i64) >> shift)
+}
+
+macro_rules! unsigned {
+    ($($type: ty),*) => {$(
+        impl Primitive for $type {
+            fn write_le(&self, data: &mut [u8], width: usize) -> Result<usize, ZclError> {
+                write_uint(data, *self as u64, width)
+            }
+
+            fn read_le(data: &[u8], width: usize) -> Result<Self, ZclError> {
+                Ok(read_uint(data, width)? as $type)
+            }
+
+            fn matches(&self, other: &Self) -> bool {
+                self == other
+            }
+        }
+    )*};
+}
+
+macro_rules! signed {
+    ($($type: ty),*) => {$(
+        impl Primitive for $type {
+            fn write_le(&self, data: &mut [u8], width: usize) -> Result<usize, ZclError> {
+                write_int(data, *self as i64, width)
+            }
+
+            fn read_le(data: &[u8], width: usize) -> Result<Self, ZclError> {
+                Ok(read_int(data, width)? as $type)
+            }
+
+            fn matches(&self, other: &Self) -> bool {
+                self == other
+            }
+        }
+    )*};
+}
+
+unsigned!(u8, u16, u32, u64);
+signed!(i8, i16, i32, i64);
+
+impl Primitive for () {
+    fn write_le(&self, _data: &mut [u8], _width: usize) -> Result<usize, ZclError> {
+        Ok(0)
+    }
+
+    fn read_le(_data: &[u8], _width: usize) -> Result<Self, ZclError> {
+        Ok(())
+    }
+
+    fn matches(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<const N: usize> Primitive for [u8; N] {
+    fn write_le(&self, data: &mut [u8], _width: usize) -> Result<usize, ZclError> {
+        write_bytes(data, self)
+    }
+
+    fn read_le(data: &[u8], _width: usize) -> Result<Self, ZclError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(read_bytes(data, N)?);
+        Ok(bytes)
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Primitive for Option<bool> {
+    fn write_le(&self, data: &mut [u8], width: usize) -> Result<usize, ZclError> {
+        let value = match self {
+            Some(false) => 0x00,
+            Some(true) => 0x01,
+            None => 0xff,
+        };
+        write_uint(data, value, width)
+    }
+
+    fn read_le(data: &[u8], width: usize) -> Result<Self, ZclError> {
+        match read_uint(data, width)? {
+            0x00 => Ok(Some(false)),
+            0x01 => Ok(Some(true)),
+            0xff => Ok(None),
+            _ => Err(ZclError::ValueOutOfRange),
+        }
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Primitive for f32 {
+    fn write_le(&self, data: &mut [u8], width: usize) -> Result<usize, ZclError> {
+        self.to_bits().write_le(data, width)
+    }
+
+    fn read_le(data: &[u8], width: usize) -> Result<Self, ZclError> {
+        Ok(f32::from_bits(u32::read_le(data, width)?))
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits() || (self.is_nan() && other.is_nan())
+    }
+}
+
+impl Primitive for f64 {
+    fn write_le(&self, data: &mut [u8], width: usize) -> Result<usize, ZclError> {
+        self.to_bits().write_le(data, width)
+    }
+
+    fn read_le(data: &[u8], width: usize) -> Result<Self, ZclError> {
+        Ok(f64::from_bits(u64::read_le(data, width)?))
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits() || (self.is_nan() && other.is_nan())
+    }
+}
+
+/// Writes a payload behind a `width` byte length prefix, as used by the string
+/// types. `None` is written as the all-ones length which marks an invalid value.
+pub(crate) fn write_prefixed(
+    data: &mut [u8],
+    payload: Option<&[u8]>,
+    width: usize,
+) -> Result<usize, ZclError> {
+    let invalid = (1u64 << (width * 8)) - 1;
+    match payload {
+        None => write_uint(data, invalid, width),
+        Some(payload) => {
+            if payload.len() as u64 >= invalid {
+                return Err(ZclError::ValueOutOfRange);
+            }
+            if data.len() < width + payload.len() {
+                return Err(ZclError::Serialization);
+            }
+            write_uint(data, payload.len() as u64, width)?;
+            write_bytes(&mut data[width..], payload)?;
+            Ok(width + payload.len())
+        }
+    }
+}
+
+/// Reads a payload behind a `width` byte length prefix. The all-ones length
+/// marks an invalid value and yields `None`.
+pub(crate) fn read_prefixed(data: &[u8], width: usize) -> Result<Option<&[u8]>, ZclError> {
+    let len = read_uint(data, width)?;
+    if len == (1u64 << (width * 8)) - 1 {
+        return Ok(None);
+    }
+    data.get(width..width + len as usize)
+        .map(Some)
+        .ok_or(ZclError::Serialization)
+}
+
+/// Sequentially encodes fields of a record or command into an output buffer.
+pub(crate) struct Writer<'b> {
+    data: &'b mut [u8],
+    offset: usize,
+}
+
+impl<'b> Writer<'b> {
+    pub(crate) fn new(data: &'b mut [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub(crate) fn uint(&mut self, value: u64, width: usize) -> Result<(), ZclError> {
+        self.offset += write_uint(&mut self.data[self.offset..], value, width)?;
+        Ok(())
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) -> Result<(), ZclError> {
+        self.uint(value as u64, 1)
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) -> Result<(), ZclError> {
+        self.uint(value as u64, 2)
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) -> Result<(), ZclError> {
+        self.offset += write_bytes(&mut self.data[self.offset..], bytes)?;
+        Ok(())
+    }
+
+    pub(crate) fn value<T: ZclEncode + ?Sized>(&mut self, value: &T) -> Result<(), ZclError> {
+        self.offset += value.to_bytes(&mut self.data[self.offset..])?;
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> usize {
+        self.offset
+    }
+}
+
+/// Sequentially decodes fields of a record or command from a received buffer.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub(crate) fn uint(&mut self, width: usize) -> Result<u64, ZclError> {
+        let value = read_uint(&self.data[self.offset..], width)?;
+        self.offset += width;
+        Ok(value)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, ZclError> {
+        Ok(self.uint(1)? as u8)
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, ZclError> {
+        Ok(self.uint(2)? as u16)
+    }
+
+    pub(crate) fn value<T: ZclDecode<'a> + ZclCompatibleType>(&mut self) -> Result<T, ZclError> {
+        let value = T::from_bytes(&self.data[self.offset..])?;
+        self.offset += value.len();
+        Ok(value)
+    }
+
+    /// Decodes a value of a received record. Character strings which are not valid
+    /// UTF-8 are kept, see [`ZclValue::decode_lossy`].
+    pub(crate) fn zcl_value(&mut self, type_id: u8) -> Result<ZclValue<'a>, ZclError> {
+        let value = ZclValue::decode_lossy(type_id, &self.data[self.offset..])?;
+        self.offset += value.len();
+        Ok(value)
+    }
+
+    /// Skips a value of a received record without decoding it, see
+    /// [`ZclValue::encoded_len`].
+    pub(crate) fn skip_value(&mut self, type_id: u8) -> Result<(), ZclError> {
+        self.offset += ZclValue::encoded_len(type_id, &self.data[self.offset..])?;
+        Ok(())
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], ZclError> {
+        let bytes = read_bytes(&self.data[self.offset..], len)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// All data which has not been read yet
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+
+    /// The number of bytes read
+    pub(crate) fn finish(self) -> usize {
+        self.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ZclError;
+    use crate::types::*;
+
+    fn round_trip<T>(value: T, bytes: &[u8])
+    where
+        T: ZclEncode + for<'a> ZclDecode<'a> + PartialEq + core::fmt::Debug,
+    {
+        let mut buf = [0u8; 16];
+        assert_eq!(value.len(), bytes.len());
+        assert_eq!(value.to_bytes(&mut buf), Ok(bytes.len()));
+        assert_eq!(&buf[..bytes.len()], bytes);
+        assert_eq!(T::from_bytes(bytes), Ok(value));
+    }
+
+    #[test]
+    fn unsigned_little_endian() {
+        round_trip(U8(0x12), &[0x12]);
+        round_trip(U16(0x1234), &[0x34, 0x12]);
+        round_trip(U24(0x123456), &[0x56, 0x34, 0x12]);
+        round_trip(U32(0x12345678), &[0x78, 0x56, 0x34, 0x12]);
+        round_trip(U40(0x12_3456_789a), &[0x9a, 0x78, 0x56, 0x34, 0x12]);
+        round_trip(U48(0xffff_ffff_fffe), &[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        round_trip(U56(0x01_0000_0000_0000), &[0, 0, 0, 0, 0, 0, 0x01]);
+        round_trip(U64(u64::MAX), &[0xff; 8]);
+    }
+
+    #[test]
+    fn signed_odd_widths_sign_extend() {
+        round_trip(I8(-1), &[0xff]);
+        round_trip(I16(-200), &[0x38, 0xff]);
+        round_trip(I24(-1), &[0xff, 0xff, 0xff]);
+        round_trip(I24(-0x80_0000), &[0x00, 0x00, 0x80]);
+        round_trip(I24(0x7f_ffff), &[0xff, 0xff, 0x7f]);
+        round_trip(I40(-2), &[0xfe, 0xff, 0xff, 0xff, 0xff]);
+        round_trip(I48(-0x8000_0000_0000), &[0, 0, 0, 0, 0, 0x80]);
+        round_trip(
+            I56(0x7f_ffff_ffff_ffff),
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+        );
+        round_trip(I64(i64::MIN), &[0, 0, 0, 0, 0, 0, 0, 0x80]);
+    }
+
+    #[test]
+    fn values_wider_than_the_type_are_rejected() {
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            U24(0x100_0000).to_bytes(&mut buf),
+            Err(ZclError::ValueOutOfRange)
+        );
+        assert_eq!(
+            U40(1 << 40).to_bytes(&mut buf),
+            Err(ZclError::ValueOutOfRange)
+        );
+        assert_eq!(
+            I24(0x80_0000).to_bytes(&mut buf),
+            Err(ZclError::ValueOutOfRange)
+        );
+        assert_eq!(
+            I24(-0x80_0001).to_bytes(&mut buf),
+            Err(ZclError::ValueOutOfRange)
+        );
+        assert_eq!(
+            I56(-(1 << 55) - 1).to_bytes(&mut buf),
+            Err(ZclError::ValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn short_buffers_fail() {
+        let mut buf = [0u8; 2];
+        assert_eq!(U24(1).to_bytes(&mut buf), Err(ZclError::Serialization));
+        assert_eq!(U24::from_bytes(&[1, 2]), Err(ZclError::Serialization));
+        assert_eq!(I40::from_bytes(&[]), Err(ZclError::Serialization));
+    }
+
+    #[test]
+    fn non_values() {
+        assert!(U24(0xff_ffff).is_non_value());
+        assert!(I24::from_bytes(&[0x00, 0x00, 0x80]).unwrap().is_non_value());
+        assert!(!I24(-1).is_non_value());
+        assert!(F32(f32::from_bits(0x7fc0_0001)).is_non_value());
+        assert!(Bool(None).is_non_value());
+        assert_eq!(Bool::from_bytes(&[0x02]), Err(ZclError::ValueOutOfRange));
+    }
+
+    #[test]
+    fn other_fixed_width_types() {
+        round_trip(Bool(Some(true)), &[0x01]);
+        round_trip(Data24([1, 2, 3]), &[1, 2, 3]);
+        round_trip(F32(1.5), &1.5f32.to_le_bytes());
+        round_trip(F64(-0.25), &(-0.25f64).to_le_bytes());
+        round_trip(
+            IeeeAddress(0x0011_2233_4455_6677),
+            &[0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00],
+        );
+        round_trip(NoData(()), &[]);
+    }
+}
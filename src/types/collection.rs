@@ -0,0 +1,450 @@
+use crate::ZclError;
+use crate::types::codec;
+use crate::types::*;
+
+/// A value which can be an element of an [`Array`], [`Set`] or [`Bag`].
+pub trait Element<'a>: ZclEncode + Copy {
+    /// The element type ID announced for a collection of `elements`. All elements of a
+    /// collection must be of the same data type.
+    fn element_type(elements: &[Self]) -> Result<u8, ZclError>;
+    /// Decodes a single element of the data type `element_type` from the start of `data`.
+    fn decode_element(element_type: u8, data: &'a [u8]) -> Result<Self, ZclError>;
+}
+
+impl<'a, T: ZclType + ZclEncode + ZclDecode<'a> + Copy> Element<'a> for T {
+    fn element_type(_elements: &[Self]) -> Result<u8, ZclError> {
+        Ok(T::ID)
+    }
+
+    fn decode_element(element_type: u8, data: &'a [u8]) -> Result<Self, ZclError> {
+        if element_type != T::ID {
+            return Err(ZclError::TypeMismatch);
+        }
+        T::from_bytes(data)
+    }
+}
+
+impl<'a> Element<'a> for ZclValue<'a> {
+    fn element_type(elements: &[Self]) -> Result<u8, ZclError> {
+        let element_type = elements.first().map_or(Unknown::ID, ZclValue::type_id);
+        if elements.iter().any(|x| x.type_id() != element_type) {
+            return Err(ZclError::TypeMismatch);
+        }
+        Ok(element_type)
+    }
+
+    fn decode_element(element_type: u8, data: &'a [u8]) -> Result<Self, ZclError> {
+        ZclValue::decode(element_type, data)
+    }
+}
+
+/// The elements of an [`Array`], [`Set`] or [`Bag`].
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Elements<'a, T> {
+    /// Elements supplied by the application, e.g. for encoding
+    Slice(&'a [T]),
+    /// Elements borrowed from a received buffer which are decoded on iteration
+    Encoded {
+        element_type: u8,
+        count: u16,
+        data: &'a [u8],
+    },
+}
+
+impl<'a, T: Element<'a>> Elements<'a, T> {
+    /// The number of elements
+    pub fn count(&self) -> usize {
+        match self {
+            Self::Slice(xs) => xs.len(),
+            Self::Encoded { count, .. } => *count as usize,
+        }
+    }
+
+    pub fn iter(&self) -> ElementIter<'a, T> {
+        match *self {
+            Self::Slice(xs) => ElementIter::Slice(xs.iter()),
+            Self::Encoded {
+                element_type,
+                count,
+                data,
+            } => ElementIter::Encoded {
+                element_type,
+                remaining: count,
+                data,
+            },
+        }
+    }
+
+    fn elements_len(&self) -> usize {
+        match self {
+            Self::Slice(xs) => xs.iter().map(|x| x.len()).sum(),
+            Self::Encoded { data, .. } => data.len(),
+        }
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        let (element_type, count) = match self {
+            Self::Slice(xs) => (T::element_type(xs)?, xs.len()),
+            Self::Encoded {
+                element_type,
+                count,
+                ..
+            } => (*element_type, *count as usize),
+        };
+        if count >= 0xffff {
+            return Err(ZclError::ValueOutOfRange);
+        }
+        let mut offset = codec::write_uint(data, element_type as u64, 1)?;
+        offset += codec::write_uint(&mut data[offset..], count as u64, 2)?;
+        match self {
+            Self::Slice(xs) => {
+                for x in xs.iter() {
+                    offset += x.to_bytes(&mut data[offset..])?;
+                }
+            }
+            Self::Encoded { data: elements, .. } => {
+                offset += codec::write_bytes(&mut data[offset..], elements)?;
+            }
+        }
+        Ok(offset)
+    }
+
+    /// Decodes the element type, count and elements of a collection. All elements are
+    /// validated once so that later iteration cannot fail.
+    fn decode(data: &'a [u8]) -> Result<Option<Self>, ZclError> {
+        let element_type = codec::read_uint(data, 1)? as u8;
+        let count = codec::read_uint(&data[1..], 2)? as u16;
+        if count == 0xffff {
+            return Ok(None);
+        }
+        let data = &data[3..];
+        let mut offset = 0;
+        for _ in 0..count {
+            let element = T::decode_element(element_type, data.get(offset..).unwrap_or_default())?;
+            offset += element.len();
+        }
+        Ok(Some(Self::Encoded {
+            element_type,
+            count,
+            data: &data[..offset],
+        }))
+    }
+}
+
+impl<'a, T: Element<'a>> IntoIterator for Elements<'a, T> {
+    type Item = T;
+    type IntoIter = ElementIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the elements of a collection, see [`Elements::iter`].
+pub enum ElementIter<'a, T> {
+    Slice(core::slice::Iter<'a, T>),
+    Encoded {
+        element_type: u8,
+        remaining: u16,
+        data: &'a [u8],
+    },
+}
+
+impl<'a, T: Element<'a>> Iterator for ElementIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self {
+            Self::Slice(xs) => xs.next().copied(),
+            Self::Encoded {
+                element_type,
+                remaining,
+                data,
+            } => {
+                if *remaining == 0 {
+                    return None;
+                }
+                *remaining -= 1;
+                let element = T::decode_element(*element_type, data).ok()?;
+                *data = &data[element.len()..];
+                Some(element)
+            }
+        }
+    }
+}
+
+macro_rules! collection_type {
+    ($name: ident, $id: literal) => {
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub struct $name<'a, T>(pub Option<Elements<'a, T>>);
+        impl<'a, T: Element<'a>> ZclType for $name<'a, T> {
+            type T = Option<Elements<'a, T>>;
+            const NON_VALUE: Option<Self::T> = Some(None);
+            const ID: u8 = $id;
+            const CLASS: DataClass = DataClass::Discrete;
+
+            fn is_non_value(&self) -> bool {
+                self.0.is_none()
+            }
+        }
+        impl<'a, T: Element<'a>> ZclCompatibleType for $name<'a, T> {
+            fn len(&self) -> usize {
+                // Element type and element count
+                3 + self.0.map_or(0, |x| x.elements_len())
+            }
+        }
+        impl<'a, T: Element<'a>> ZclEncode for $name<'a, T> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                match self.0 {
+                    Some(x) => x.encode(data),
+                    None => {
+                        codec::write_uint(data, T::element_type(&[])? as u64, 1)?;
+                        codec::write_uint(&mut data[1..], 0xffff, 2)?;
+                        Ok(3)
+                    }
+                }
+            }
+        }
+        impl<'a, T: Element<'a>> ZclDecode<'a> for $name<'a, T> {
+            fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+                Ok(Self(Elements::decode(data)?))
+            }
+        }
+    };
+}
+
+collection_type!(Array, 0x48);
+collection_type!(Set, 0x50);
+collection_type!(Bag, 0x51);
+
+pub trait StructureCompatibleType: ZclEncode {
+    // u16 is required for Structures by the Zcl spec
+    fn num_members(&self) -> u16;
+}
+
+/// Decoding of the members of a [`Structure`]. The members are encoded by
+/// [`ZclEncode::to_bytes`], each prefixed with its type ID.
+pub trait StructureDecode<'a>: Sized {
+    /// Decodes `count` members from the start of `data`.
+    fn from_members(count: u16, data: &'a [u8]) -> Result<Self, ZclError>;
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Structure<T>(pub Option<T>);
+impl<T: StructureCompatibleType> ZclType for Structure<T> {
+    type T = Option<T>;
+    const NON_VALUE: Option<Self::T> = Some(None);
+    const ID: u8 = 0x4c;
+    const CLASS: DataClass = DataClass::Discrete;
+
+    fn is_non_value(&self) -> bool {
+        self.0.is_none()
+    }
+}
+impl<T: StructureCompatibleType> ZclCompatibleType for Structure<T> {
+    fn len(&self) -> usize {
+        match self.0 {
+            Some(ref x) => x.len() + 2,
+            None => 2,
+        }
+    }
+}
+impl<T: StructureCompatibleType> ZclEncode for Structure<T> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        match self.0 {
+            Some(ref x) => {
+                if x.num_members() == 0xffff {
+                    return Err(ZclError::ValueOutOfRange);
+                }
+                codec::write_uint(data, x.num_members() as u64, 2)?;
+                Ok(2 + x.to_bytes(data.get_mut(2..).unwrap_or_default())?)
+            }
+            None => codec::write_uint(data, 0xffff, 2),
+        }
+    }
+}
+impl<'a, T: StructureCompatibleType + StructureDecode<'a>> ZclDecode<'a> for Structure<T> {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+        match codec::read_uint(data, 2)? as u16 {
+            0xffff => Ok(Self(None)),
+            count => Ok(Self(Some(T::from_members(count, &data[2..])?))),
+        }
+    }
+}
+
+/// The members of a [`Structure`] whose layout is only known at runtime.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Members<'a> {
+    /// Members supplied by the application, e.g. for encoding
+    Slice(&'a [ZclValue<'a>]),
+    /// Members borrowed from a received buffer which are decoded on iteration
+    Encoded { count: u16, data: &'a [u8] },
+}
+
+impl<'a> Members<'a> {
+    pub fn iter(&self) -> MemberIter<'a> {
+        match *self {
+            Self::Slice(xs) => MemberIter::Slice(xs.iter()),
+            Self::Encoded { count, data } => MemberIter::Encoded {
+                remaining: count,
+                data,
+            },
+        }
+    }
+}
+
+impl<'a> IntoIterator for Members<'a> {
+    type Item = ZclValue<'a>;
+    type IntoIter = MemberIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl ZclCompatibleType for Members<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Slice(xs) => xs.iter().map(|x| 1 + x.len()).sum(),
+            Self::Encoded { data, .. } => data.len(),
+        }
+    }
+}
+
+impl ZclEncode for Members<'_> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        match self {
+            Self::Slice(xs) => {
+                let mut offset = 0;
+                for x in xs.iter() {
+                    offset += codec::write_uint(&mut data[offset..], x.type_id() as u64, 1)?;
+                    offset += x.encode(&mut data[offset..])?;
+                }
+                Ok(offset)
+            }
+            Self::Encoded { data: members, .. } => codec::write_bytes(data, members),
+        }
+    }
+}
+
+impl StructureCompatibleType for Members<'_> {
+    fn num_members(&self) -> u16 {
+        match self {
+            Self::Slice(xs) => xs.len().min(0xffff) as u16,
+            Self::Encoded { count, .. } => *count,
+        }
+    }
+}
+
+impl<'a> StructureDecode<'a> for Members<'a> {
+    fn from_members(count: u16, data: &'a [u8]) -> Result<Self, ZclError> {
+        let mut offset = 0;
+        for _ in 0..count {
+            let type_id = codec::read_uint(data.get(offset..).unwrap_or_default(), 1)? as u8;
+            offset += 1 + ZclValue::decode(type_id, &data[offset + 1..])?.len();
+        }
+        Ok(Self::Encoded {
+            count,
+            data: &data[..offset],
+        })
+    }
+}
+
+/// Iterator over the members of a structure, see [`Members::iter`].
+pub enum MemberIter<'a> {
+    Slice(core::slice::Iter<'a, ZclValue<'a>>),
+    Encoded { remaining: u16, data: &'a [u8] },
+}
+
+impl<'a> Iterator for MemberIter<'a> {
+    type Item = ZclValue<'a>;
+
+    fn next(&mut self) -> Option<ZclValue<'a>> {
+        match self {
+            Self::Slice(xs) => xs.next().copied(),
+            Self::Encoded { remaining, data } => {
+                if *remaining == 0 {
+                    return None;
+                }
+                *remaining -= 1;
+                let (type_id, rest) = data.split_first()?;
+                let member = ZclValue::decode(*type_id, rest).ok()?;
+                *data = &rest[member.len()..];
+                Some(member)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_round_trip() {
+        let array = Array(Some(Elements::Slice(&[U16(1), U16(0x0203)])));
+        let mut buf = [0u8; 8];
+        assert_eq!(array.len(), 7);
+        assert_eq!(array.to_bytes(&mut buf), Ok(7));
+        assert_eq!(buf[..7], [0x21, 0x02, 0x00, 0x01, 0x00, 0x03, 0x02]);
+
+        let decoded = Array::<U16>::from_bytes(&buf).unwrap();
+        assert_eq!(decoded.len(), 7);
+        let elements = decoded.0.unwrap();
+        assert_eq!(elements.count(), 2);
+        assert!(elements.iter().eq([U16(1), U16(0x0203)]));
+    }
+
+    #[test]
+    fn dynamic_elements() {
+        let data = [0x42, 0x02, 0x00, 0x01, b'a', 0x02, b'b', b'c', 0xee];
+        let set = Set::<ZclValue>::from_bytes(&data).unwrap();
+        assert_eq!(set.len(), 8);
+        assert!(set.0.unwrap().iter().eq([
+            ZclValue::CharacterString(CharacterString(Some("a"))),
+            ZclValue::CharacterString(CharacterString(Some("bc"))),
+        ]));
+        // Mixed element types cannot be encoded
+        let mixed = [ZclValue::U8(U8(1)), ZclValue::U16(U16(1))];
+        let bag = Bag(Some(Elements::Slice(&mixed)));
+        assert_eq!(bag.to_bytes(&mut [0u8; 8]), Err(ZclError::TypeMismatch));
+    }
+
+    #[test]
+    fn invalid_collections() {
+        assert_eq!(
+            Array::<U8>::from_bytes(&[0x20, 0xff, 0xff]),
+            Ok(Array(None))
+        );
+        assert_eq!(
+            Array::<U8>::from_bytes(&[0x21, 0x01, 0x00, 0x00, 0x00]),
+            Err(ZclError::TypeMismatch)
+        );
+        // Fewer elements than announced
+        assert_eq!(
+            Array::<U8>::from_bytes(&[0x20, 0x03, 0x00, 0x01, 0x02]),
+            Err(ZclError::Serialization)
+        );
+        let mut buf = [0u8; 3];
+        assert_eq!(Array::<U8>(None).to_bytes(&mut buf), Ok(3));
+        assert_eq!(buf, [0x20, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn structure_round_trip() {
+        let members = [ZclValue::U8(U8(7)), ZclValue::Bool(Bool(Some(true)))];
+        let structure = Structure(Some(Members::Slice(&members)));
+        let mut buf = [0u8; 8];
+        assert_eq!(structure.len(), 6);
+        assert_eq!(structure.to_bytes(&mut buf), Ok(6));
+        assert_eq!(buf[..6], [0x02, 0x00, 0x20, 0x07, 0x10, 0x01]);
+
+        let decoded = Structure::<Members>::from_bytes(&buf).unwrap();
+        assert_eq!(decoded.len(), 6);
+        assert!(decoded.0.unwrap().iter().eq(members));
+        assert_eq!(
+            Structure::<Members>::from_bytes(&[0x01, 0x00, 0x99, 0x00]),
+            Err(ZclError::UnknownDataType(0x99))
+        );
+    }
+}
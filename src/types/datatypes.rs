@@ -1,17 +1,75 @@
+use core::cmp::Ordering;
+
+use crate::ZclError;
+use crate::types::codec::{self, Primitive};
+
+#[allow(clippy::len_without_is_empty)]
 pub trait ZclCompatibleType {
+    /// The number of bytes this value occupies on the wire
     fn len(&self) -> usize;
-    //fn to_bytes(self, data: &mut [u8]) -> Result<(), ZclError>;
-    //fn from_bytes(data: &[u8]) -> Result<Self, ZclError>;
+}
+
+/// Serializes a value into its little-endian over-the-air representation.
+pub trait ZclEncode: ZclCompatibleType {
+    /// Writes the value to the start of `data` and returns the number of bytes written.
+    /// Fails with [`ZclError::Serialization`] if `data` is too short and with
+    /// [`ZclError::ValueOutOfRange`] if the value does not fit the wire format.
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError>;
+}
+
+/// Deserializes a value from the start of a received buffer. The number of
+/// bytes consumed is given by [`ZclCompatibleType::len`] of the returned value.
+pub trait ZclDecode<'a>: Sized {
+    fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError>;
+}
+
+/// The class of a data type, which e.g. determines whether reports carry a
+/// reportable change
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum DataClass {
+    /// No data, e.g. [`NoData`] and [`Unknown`]
+    Null,
+    /// Values for which a difference is meaningful: integers, floats and times
+    Analog,
+    /// Values which can only be equal or not: bitmaps, enums, strings, collections and IDs
+    Discrete,
+}
+
+/// The class of the data type with the ID `type_id`, `None` for reserved IDs.
+pub const fn data_class(type_id: u8) -> Option<DataClass> {
+    match type_id {
+        0x00 | 0xff => Some(DataClass::Null),
+        0x20..=0x2f | 0x38..=0x3a | 0xe0..=0xe2 => Some(DataClass::Analog),
+        0x08..=0x10 | 0x18..=0x1f | 0x30 | 0x31 | 0x41..=0x44 | 0x48 | 0x4c | 0x50 | 0x51 => {
+            Some(DataClass::Discrete)
+        }
+        0xe8..=0xea | 0xf0 | 0xf1 => Some(DataClass::Discrete),
+        _ => None,
+    }
 }
 
 pub trait ZclType: ZclCompatibleType {
     type T;
     const NON_VALUE: Option<Self::T>;
     const ID: u8;
+    const CLASS: DataClass;
+
+    /// Whether this value is the non-value of the data type
+    fn is_non_value(&self) -> bool;
+
+    /// Orders two values for range checks, `None` if the data type has no ordering.
+    fn compare(&self, _other: &Self) -> Option<Ordering> {
+        None
+    }
+
+    /// The size in bytes which is limited by [`AttributeRange::Size`](crate::types::AttributeRange::Size)
+    fn size(&self) -> usize {
+        self.len()
+    }
 }
 
 macro_rules! basic_type {
-    ($name: ident, $type: ty, $non: expr, $id: literal, $len: literal) => {
+    ($name: ident, $type: ty, $non: expr, $id: literal, $len: literal, $class: ident) => {
         #[derive(PartialEq, PartialOrd, Debug, Copy, Clone)]
         pub struct $name(pub $type);
         impl ZclCompatibleType for $name {
@@ -20,57 +78,233 @@ macro_rules! basic_type {
             }
         }
 
+        impl ZclEncode for $name {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                self.0.write_le(data, $len)
+            }
+        }
+
+        impl ZclDecode<'_> for $name {
+            fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+                Ok(Self(Primitive::read_le(data, $len)?))
+            }
+        }
+
         impl ZclType for $name {
             type T = $type;
             const NON_VALUE: Option<Self::T> = $non;
             const ID: u8 = $id;
+            const CLASS: DataClass = DataClass::$class;
+
+            fn is_non_value(&self) -> bool {
+                Self::NON_VALUE.is_some_and(|x| self.0.matches(&x))
+            }
+
+            fn compare(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
         }
     };
 }
 
-pub trait ZclEnum {
+pub trait ZclEnum: Sized + Copy {
+    /// The integer type backing the enumeration on the wire
+    type Repr: Copy + PartialOrd;
     const NON_VALUE: Self;
+    fn into_raw(self) -> Self::Repr;
+    fn try_from_raw(value: Self::Repr) -> Option<Self>;
 }
 
-basic_type!(NoData, (), None, 0x00, 0x0);
-
-basic_type!(Data8, [u8; 1], None, 0x08, 0x1);
-basic_type!(Data16, [u8; 2], None, 0x09, 0x2);
-basic_type!(Data24, [u8; 3], None, 0x0a, 0x3);
-basic_type!(Data32, [u8; 4], None, 0x0b, 0x4);
-basic_type!(Data40, [u8; 5], None, 0x0c, 0x5);
-basic_type!(Data48, [u8; 6], None, 0x0d, 0x6);
-basic_type!(Data56, [u8; 7], None, 0x0e, 0x7);
-basic_type!(Data64, [u8; 8], None, 0x0f, 0x8);
-
-basic_type!(Bool, Option<bool>, Some(None), 0x10, 0x1);
-
-basic_type!(Bitmap8, u8, None, 0x18, 0x1);
-basic_type!(Bitmap16, u16, None, 0x19, 0x2);
-basic_type!(Bitmap24, u32, None, 0x1a, 0x3);
-basic_type!(Bitmap32, u32, None, 0x1b, 0x4);
-basic_type!(Bitmap40, u64, None, 0x1c, 0x5);
-basic_type!(Bitmap48, u64, None, 0x1d, 0x6);
-basic_type!(Bitmap56, u64, None, 0x1e, 0x7);
-basic_type!(Bitmap64, u64, None, 0x1f, 0x8);
-
-basic_type!(U8, u8, Some(0xff), 0x20, 0x1);
-basic_type!(U16, u16, Some(0xffff), 0x21, 0x2);
-basic_type!(U24, u32, Some(0xffffff), 0x22, 0x3);
-basic_type!(U32, u32, Some(0xffffffff), 0x23, 0x4);
-basic_type!(U40, u64, Some(0xffffffffff), 0x24, 0x5);
-basic_type!(U48, u64, Some(0xffffffffffff), 0x25, 0x6);
-basic_type!(U56, u64, Some(0xffffffffffffff), 0x26, 0x7);
-basic_type!(U64, u64, Some(0xffffffffffffffff), 0x27, 0x8);
-
-basic_type!(I8, i8, Some(0x80u8 as i8), 0x28, 0x1);
-basic_type!(I16, i16, Some(0x8000u16 as i16), 0x29, 0x2);
-basic_type!(I24, i32, Some(0xff800000u32 as i32), 0x2a, 0x3);
-basic_type!(I32, i32, Some(0x80000000u32 as i32), 0x2b, 0x4);
-basic_type!(I40, i64, Some(0xffffff8000000000u64 as i64), 0x2c, 0x5);
-basic_type!(I48, i64, Some(0xffff800000000000u64 as i64), 0x2d, 0x6);
-basic_type!(I56, i64, Some(0xff80000000000000u64 as i64), 0x2e, 0x7);
-basic_type!(I64, i64, Some(0x8000000000000000u64 as i64), 0x2f, 0x8);
+macro_rules! raw_enum {
+    ($type: ty, $non: literal) => {
+        // Plain integers serve as enumerations whose variants are not known statically
+        impl ZclEnum for $type {
+            type Repr = $type;
+            const NON_VALUE: Self = $non;
+            fn into_raw(self) -> Self::Repr {
+                self
+            }
+            fn try_from_raw(value: Self::Repr) -> Option<Self> {
+                Some(value)
+            }
+        }
+    };
+}
+
+raw_enum!(u8, 0xff);
+raw_enum!(u16, 0xffff);
+
+/// A value of the enumeration `T` which keeps the raw values `T` has no variant for,
+/// e.g. vendor values or those added by newer revisions of the specification. Decoding
+/// an `Enum8<Lossless<T>>` never fails on unknown values.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Lossless<T: ZclEnum> {
+    Known(T),
+    Unknown(T::Repr),
+}
+
+impl<T: ZclEnum> Lossless<T> {
+    /// The known variant, `None` for unknown values
+    pub fn known(self) -> Option<T> {
+        match self {
+            Self::Known(x) => Some(x),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+impl<T: ZclEnum> From<T> for Lossless<T> {
+    fn from(value: T) -> Self {
+        Self::Known(value)
+    }
+}
+
+impl<T: ZclEnum> ZclEnum for Lossless<T> {
+    type Repr = T::Repr;
+    const NON_VALUE: Self = Self::Known(T::NON_VALUE);
+    fn into_raw(self) -> Self::Repr {
+        match self {
+            Self::Known(x) => x.into_raw(),
+            Self::Unknown(x) => x,
+        }
+    }
+    fn try_from_raw(value: Self::Repr) -> Option<Self> {
+        Some(T::try_from_raw(value).map_or(Self::Unknown(value), Self::Known))
+    }
+}
+
+basic_type!(NoData, (), None, 0x00, 0x0, Null);
+
+basic_type!(Data8, [u8; 1], None, 0x08, 0x1, Discrete);
+basic_type!(Data16, [u8; 2], None, 0x09, 0x2, Discrete);
+basic_type!(Data24, [u8; 3], None, 0x0a, 0x3, Discrete);
+basic_type!(Data32, [u8; 4], None, 0x0b, 0x4, Discrete);
+basic_type!(Data40, [u8; 5], None, 0x0c, 0x5, Discrete);
+basic_type!(Data48, [u8; 6], None, 0x0d, 0x6, Discrete);
+basic_type!(Data56, [u8; 7], None, 0x0e, 0x7, Discrete);
+basic_type!(Data64, [u8; 8], None, 0x0f, 0x8, Discrete);
+
+basic_type!(Bool, Option<bool>, Some(None), 0x10, 0x1, Discrete);
+
+/// The bits of a bitmap type. Generated flag types mask out the reserved bits when
+/// converting from the raw value.
+pub trait ZclBitmap: Sized + Copy {
+    /// The integer type backing the bitmap on the wire
+    type Repr: Copy + PartialOrd;
+    fn into_raw(self) -> Self::Repr;
+    fn from_raw(value: Self::Repr) -> Self;
+}
+
+macro_rules! raw_bitmap {
+    ($($type: ty),*) => {$(
+        // Plain integers serve as bitmaps whose bits are not known statically
+        impl ZclBitmap for $type {
+            type Repr = $type;
+            fn into_raw(self) -> Self::Repr {
+                self
+            }
+            fn from_raw(value: Self::Repr) -> Self {
+                value
+            }
+        }
+    )*};
+}
+
+raw_bitmap!(u8, u16, u32, u64);
+
+macro_rules! bitmap_type {
+    ($name: ident, $repr: ty, $id: literal, $len: literal) => {
+        #[derive(PartialEq, PartialOrd, Debug, Copy, Clone)]
+        pub struct $name<T: ZclBitmap<Repr = $repr> = $repr>(pub T);
+        impl<T: ZclBitmap<Repr = $repr>> ZclCompatibleType for $name<T> {
+            fn len(&self) -> usize {
+                $len
+            }
+        }
+
+        impl<T: ZclBitmap<Repr = $repr>> ZclEncode for $name<T> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                self.0.into_raw().write_le(data, $len)
+            }
+        }
+
+        impl<T: ZclBitmap<Repr = $repr>> ZclDecode<'_> for $name<T> {
+            fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+                Ok(Self(T::from_raw(Primitive::read_le(data, $len)?)))
+            }
+        }
+
+        impl<T: ZclBitmap<Repr = $repr>> ZclType for $name<T> {
+            type T = T;
+            const NON_VALUE: Option<Self::T> = None;
+            const ID: u8 = $id;
+            const CLASS: DataClass = DataClass::Discrete;
+
+            fn is_non_value(&self) -> bool {
+                false
+            }
+
+            fn compare(&self, other: &Self) -> Option<Ordering> {
+                self.0.into_raw().partial_cmp(&other.0.into_raw())
+            }
+        }
+    };
+}
+
+bitmap_type!(Bitmap8, u8, 0x18, 0x1);
+bitmap_type!(Bitmap16, u16, 0x19, 0x2);
+bitmap_type!(Bitmap24, u32, 0x1a, 0x3);
+bitmap_type!(Bitmap32, u32, 0x1b, 0x4);
+bitmap_type!(Bitmap40, u64, 0x1c, 0x5);
+bitmap_type!(Bitmap48, u64, 0x1d, 0x6);
+bitmap_type!(Bitmap56, u64, 0x1e, 0x7);
+bitmap_type!(Bitmap64, u64, 0x1f, 0x8);
+
+basic_type!(U8, u8, Some(0xff), 0x20, 0x1, Analog);
+basic_type!(U16, u16, Some(0xffff), 0x21, 0x2, Analog);
+basic_type!(U24, u32, Some(0xffffff), 0x22, 0x3, Analog);
+basic_type!(U32, u32, Some(0xffffffff), 0x23, 0x4, Analog);
+basic_type!(U40, u64, Some(0xffffffffff), 0x24, 0x5, Analog);
+basic_type!(U48, u64, Some(0xffffffffffff), 0x25, 0x6, Analog);
+basic_type!(U56, u64, Some(0xffffffffffffff), 0x26, 0x7, Analog);
+basic_type!(U64, u64, Some(0xffffffffffffffff), 0x27, 0x8, Analog);
+
+basic_type!(I8, i8, Some(0x80u8 as i8), 0x28, 0x1, Analog);
+basic_type!(I16, i16, Some(0x8000u16 as i16), 0x29, 0x2, Analog);
+basic_type!(I24, i32, Some(0xff800000u32 as i32), 0x2a, 0x3, Analog);
+basic_type!(I32, i32, Some(0x80000000u32 as i32), 0x2b, 0x4, Analog);
+basic_type!(
+    I40,
+    i64,
+    Some(0xffffff8000000000u64 as i64),
+    0x2c,
+    0x5,
+    Analog
+);
+basic_type!(
+    I48,
+    i64,
+    Some(0xffff800000000000u64 as i64),
+    0x2d,
+    0x6,
+    Analog
+);
+basic_type!(
+    I56,
+    i64,
+    Some(0xff80000000000000u64 as i64),
+    0x2e,
+    0x7,
+    Analog
+);
+basic_type!(
+    I64,
+    i64,
+    Some(0x8000000000000000u64 as i64),
+    0x2f,
+    0x8,
+    Analog
+);
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub struct Enum8<T: ZclEnum>(pub T);
@@ -78,12 +312,33 @@ impl<T: ZclEnum> ZclType for Enum8<T> {
     type T = T;
     const NON_VALUE: Option<Self::T> = Some(T::NON_VALUE);
     const ID: u8 = 0x30;
+    const CLASS: DataClass = DataClass::Discrete;
+
+    fn is_non_value(&self) -> bool {
+        self.0.into_raw() == T::NON_VALUE.into_raw()
+    }
+
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        self.0.into_raw().partial_cmp(&other.0.into_raw())
+    }
 }
 impl<T: ZclEnum> ZclCompatibleType for Enum8<T> {
     fn len(&self) -> usize {
         0x1
     }
 }
+impl<T: ZclEnum<Repr = u8>> ZclEncode for Enum8<T> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        self.0.into_raw().write_le(data, 0x1)
+    }
+}
+impl<T: ZclEnum<Repr = u8>> ZclDecode<'_> for Enum8<T> {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        T::try_from_raw(u8::read_le(data, 0x1)?)
+            .map(Self)
+            .ok_or(ZclError::ValueOutOfRange)
+    }
+}
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub struct Enum16<T: ZclEnum>(pub T);
@@ -91,112 +346,258 @@ impl<T: ZclEnum> ZclType for Enum16<T> {
     type T = T;
     const NON_VALUE: Option<Self::T> = Some(T::NON_VALUE);
     const ID: u8 = 0x31;
+    const CLASS: DataClass = DataClass::Discrete;
+
+    fn is_non_value(&self) -> bool {
+        self.0.into_raw() == T::NON_VALUE.into_raw()
+    }
+
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        self.0.into_raw().partial_cmp(&other.0.into_raw())
+    }
 }
 impl<T: ZclEnum> ZclCompatibleType for Enum16<T> {
     fn len(&self) -> usize {
         0x2
     }
 }
+impl<T: ZclEnum<Repr = u16>> ZclEncode for Enum16<T> {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        self.0.into_raw().write_le(data, 0x2)
+    }
+}
+impl<T: ZclEnum<Repr = u16>> ZclDecode<'_> for Enum16<T> {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        T::try_from_raw(u16::read_le(data, 0x2)?)
+            .map(Self)
+            .ok_or(ZclError::ValueOutOfRange)
+    }
+}
 
-// basic_type!(F16, f16, Some(f16::NAN), 0x38, 0x2);
-basic_type!(F32, f32, Some(f32::NAN), 0x39, 0x4);
-basic_type!(F64, f64, Some(f64::NAN), 0x3a, 0x8);
+/// A semi-precision (IEEE 754 binary16) floating point number, stored as its raw bits.
+/// Comparisons are performed on the numeric value, so like for [`F32`] a NaN never
+/// equals anything. Any NaN is considered the non-value.
+#[derive(Debug, Copy, Clone)]
+pub struct SemiFloat(pub u16);
+impl SemiFloat {
+    pub const NAN: Self = Self(0x7e00);
+    pub const INFINITY: Self = Self(0x7c00);
+    pub const NEG_INFINITY: Self = Self(0xfc00);
 
-pub struct OctetString<'a>(pub Option<&'a [u8]>);
-impl<'a> ZclType for OctetString<'a> {
-    type T = Option<&'a [u8]>;
-    const NON_VALUE: Option<Self::T> = Some(None);
-    const ID: u8 = 0x41;
-}
-impl ZclCompatibleType for OctetString<'_> {
-    fn len(&self) -> usize {
-        match self.0 {
-            Some(x) => x.len() + 1,
-            None => 1,
+    pub const fn is_nan(self) -> bool {
+        self.0 & 0x7c00 == 0x7c00 && self.0 & 0x03ff != 0
+    }
+
+    /// Converts to a single precision float. Every semi-precision value is exactly
+    /// representable, NaN payloads are preserved.
+    pub const fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exp = ((self.0 >> 10) & 0x1f) as u32;
+        let mut mant = (self.0 & 0x03ff) as u32;
+        let bits = match exp {
+            0 if mant == 0 => sign,
+            0 => {
+                // Subnormal, normalize it into the wider exponent range
+                let mut exp = 127 - 15 + 1;
+                while mant & 0x0400 == 0 {
+                    mant <<= 1;
+                    exp -= 1;
+                }
+                sign | (exp << 23) | ((mant & 0x03ff) << 13)
+            }
+            0x1f => sign | 0x7f80_0000 | (mant << 13),
+            _ => sign | ((exp + 127 - 15) << 23) | (mant << 13),
+        };
+        f32::from_bits(bits)
+    }
+
+    /// Converts from a single precision float, rounding to the nearest representable
+    /// value (ties to even). Values too large become infinite, NaN stays NaN.
+    pub const fn from_f32(value: f32) -> Self {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exp = ((bits >> 23) & 0xff) as i32;
+        let mant = bits & 0x007f_ffff;
+
+        if exp == 0xff {
+            return match mant {
+                0 => Self(sign | 0x7c00),
+                // Keep the upper payload bits and make sure the result is still a NaN
+                _ => Self(sign | 0x7e00 | (mant >> 13) as u16),
+            };
         }
+
+        let exp = exp - 127 + 15;
+        if exp >= 0x1f {
+            return Self(sign | 0x7c00);
+        }
+        if exp <= 0 {
+            if exp < -10 {
+                return Self(sign);
+            }
+            // Subnormal result, shift in the implicit leading one
+            let mant = mant | 0x0080_0000;
+            let shift = (14 - exp) as u32;
+            let half = mant >> shift;
+            let rem = mant & ((1 << shift) - 1);
+            let halfway = 1 << (shift - 1);
+            let round = (rem > halfway || (rem == halfway && half & 1 == 1)) as u32;
+            // A carry out of the mantissa correctly produces the smallest normal number
+            return Self(sign | (half + round) as u16);
+        }
+
+        let half = ((exp as u32) << 10) | (mant >> 13);
+        let rem = mant & 0x1fff;
+        let round = (rem > 0x1000 || (rem == 0x1000 && half & 1 == 1)) as u32;
+        // A carry out of the mantissa correctly rounds up to infinity
+        Self(sign | (half + round) as u16)
     }
 }
-
-pub struct CharacterString<'a>(pub Option<&'a str>);
-impl<'a> ZclType for CharacterString<'a> {
-    type T = Option<&'a str>;
-    const NON_VALUE: Option<Self::T> = Some(None);
-    const ID: u8 = 0x42;
+impl From<f32> for SemiFloat {
+    fn from(value: f32) -> Self {
+        Self::from_f32(value)
+    }
 }
-impl ZclCompatibleType for CharacterString<'_> {
-    fn len(&self) -> usize {
-        match self.0 {
-            Some(x) => x.len() + 1,
-            None => 1,
-        }
+impl From<SemiFloat> for f32 {
+    fn from(value: SemiFloat) -> Self {
+        value.to_f32()
     }
 }
-
-pub struct LongOctetString<'a>(pub Option<&'a [u8]>);
-impl<'a> ZclType for LongOctetString<'a> {
-    type T = Option<&'a [u8]>;
-    const NON_VALUE: Option<Self::T> = Some(None);
-    const ID: u8 = 0x43;
+impl PartialEq for SemiFloat {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_f32() == other.to_f32()
+    }
 }
-impl ZclCompatibleType for LongOctetString<'_> {
-    fn len(&self) -> usize {
-        match self.0 {
-            Some(x) => x.len() + 2,
-            None => 2,
-        }
+impl PartialOrd for SemiFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
     }
 }
+impl ZclType for SemiFloat {
+    type T = u16;
+    const NON_VALUE: Option<Self::T> = Some(SemiFloat::NAN.0);
+    const ID: u8 = 0x38;
+    const CLASS: DataClass = DataClass::Analog;
+
+    fn is_non_value(&self) -> bool {
+        self.is_nan()
+    }
 
-pub struct LongCharacterString<'a>(pub Option<&'a str>);
-impl<'a> ZclType for LongCharacterString<'a> {
-    type T = Option<&'a str>;
-    const NON_VALUE: Option<Self::T> = Some(None);
-    const ID: u8 = 0x44;
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        self.partial_cmp(other)
+    }
 }
-impl ZclCompatibleType for LongCharacterString<'_> {
+impl ZclCompatibleType for SemiFloat {
     fn len(&self) -> usize {
-        match self.0 {
-            Some(x) => x.len() + 2,
-            None => 2,
-        }
+        0x2
     }
 }
-
-pub struct Array<'a, T>(pub Option<&'a [T]>);
-impl<'a, T: ZclCompatibleType> ZclType for Array<'a, T> {
-    type T = Option<&'a [T]>;
-    const NON_VALUE: Option<Self::T> = Some(None);
-    const ID: u8 = 0x48;
+impl ZclEncode for SemiFloat {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        self.0.write_le(data, 0x2)
+    }
 }
-impl<T: ZclCompatibleType> ZclCompatibleType for Array<'_, T> {
-    fn len(&self) -> usize {
-        match self.0 {
-            Some(xs) => 2 + xs.iter().map(|x| x.len()).sum::<usize>(),
-            None => 2,
-        }
+impl ZclDecode<'_> for SemiFloat {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        Ok(Self(u16::read_le(data, 0x2)?))
     }
 }
 
-pub trait StructureCompatibleType: ZclCompatibleType {
-    // u16 is required for Structures by the Zcl spec
-    fn num_members(&self) -> u16;
+basic_type!(F32, f32, Some(f32::NAN), 0x39, 0x4, Analog);
+basic_type!(F64, f64, Some(f64::NAN), 0x3a, 0x8, Analog);
+
+macro_rules! string_type {
+    ($name: ident, $payload: ty, $id: literal, $width: literal, $as_bytes: expr, $from_bytes: expr) => {
+        #[derive(PartialEq, Eq, Debug, Copy, Clone)]
+        pub struct $name<'a>(pub Option<&'a $payload>);
+        impl<'a> ZclType for $name<'a> {
+            type T = Option<&'a $payload>;
+            const NON_VALUE: Option<Self::T> = Some(None);
+            const ID: u8 = $id;
+            const CLASS: DataClass = DataClass::Discrete;
+
+            fn is_non_value(&self) -> bool {
+                self.0.is_none()
+            }
+
+            fn size(&self) -> usize {
+                self.0.map_or(0, |x| x.len())
+            }
+        }
+        impl ZclCompatibleType for $name<'_> {
+            fn len(&self) -> usize {
+                match self.0 {
+                    Some(x) => x.len() + $width,
+                    None => $width,
+                }
+            }
+        }
+        impl ZclEncode for $name<'_> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                codec::write_prefixed(data, self.0.map($as_bytes), $width)
+            }
+        }
+        impl<'a> ZclDecode<'a> for $name<'a> {
+            fn from_bytes(data: &'a [u8]) -> Result<Self, ZclError> {
+                Ok(Self(match codec::read_prefixed(data, $width)? {
+                    Some(x) => Some($from_bytes(x)?),
+                    None => None,
+                }))
+            }
+        }
+    };
 }
 
-pub struct Structure<T>(Option<T>);
-impl<T: StructureCompatibleType> ZclType for Structure<T> {
-    type T = Option<T>;
-    const NON_VALUE: Option<Self::T> = Some(None);
-    const ID: u8 = 0x4c;
+fn raw(data: &[u8]) -> Result<&[u8], ZclError> {
+    Ok(data)
 }
-impl<T: StructureCompatibleType> ZclCompatibleType for Structure<T> {
-    fn len(&self) -> usize {
-        match self.0 {
-            Some(ref x) => x.num_members() as usize + 2,
-            None => 2,
+
+fn utf8(data: &[u8]) -> Result<&str, ZclError> {
+    core::str::from_utf8(data).map_err(|_| ZclError::InvalidUtf8)
+}
+
+string_type!(OctetString, [u8], 0x41, 0x1, |x| x, raw);
+string_type!(CharacterString, str, 0x42, 0x1, str::as_bytes, utf8);
+string_type!(LongOctetString, [u8], 0x43, 0x2, |x| x, raw);
+string_type!(LongCharacterString, str, 0x44, 0x2, str::as_bytes, utf8);
+
+// Character strings which are decoded without validating their contents.
+// Some devices send e.g. Latin-1 encoded manufacturer names, which would be
+// rejected as [`CharacterString`].
+string_type!(LossyCharacterString, [u8], 0x42, 0x1, |x| x, raw);
+string_type!(LossyLongCharacterString, [u8], 0x44, 0x2, |x| x, raw);
+
+macro_rules! lossy_string {
+    ($name: ident) => {
+        impl<'a> $name<'a> {
+            /// Returns the payload if it is valid UTF-8.
+            pub fn as_str(&self) -> Option<&'a str> {
+                self.0.and_then(|x| core::str::from_utf8(x).ok())
+            }
+
+            /// Iterates over the characters of the payload. Valid UTF-8 sequences are
+            /// decoded as such, all other bytes are interpreted as Latin-1.
+            pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+                self.0.unwrap_or_default().utf8_chunks().flat_map(|chunk| {
+                    chunk
+                        .valid()
+                        .chars()
+                        .chain(chunk.invalid().iter().map(|&b| char::from(b)))
+                })
+            }
         }
-    }
+        impl core::fmt::Display for $name<'_> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                use core::fmt::Write;
+                self.chars().try_for_each(|c| f.write_char(c))
+            }
+        }
+    };
 }
 
+lossy_string!(LossyCharacterString);
+lossy_string!(LossyLongCharacterString);
+
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub struct TimeOfDay {
     pub hours: u8,
@@ -217,12 +618,33 @@ impl ZclType for TimeOfDay {
     type T = TimeOfDay;
     const NON_VALUE: Option<Self::T> = Some(TimeOfDay::INVALID);
     const ID: u8 = 0xe0;
+    const CLASS: DataClass = DataClass::Analog;
+
+    fn is_non_value(&self) -> bool {
+        *self == Self::INVALID
+    }
 }
 impl ZclCompatibleType for TimeOfDay {
     fn len(&self) -> usize {
         0x4
     }
 }
+impl ZclEncode for TimeOfDay {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        [self.hours, self.minutes, self.seconds, self.hundredths].write_le(data, 0x4)
+    }
+}
+impl ZclDecode<'_> for TimeOfDay {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let [hours, minutes, seconds, hundredths] = Primitive::read_le(data, 0x4)?;
+        Ok(Self {
+            hours,
+            minutes,
+            seconds,
+            hundredths,
+        })
+    }
+}
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub struct Date {
@@ -248,17 +670,276 @@ impl ZclType for Date {
     type T = Date;
     const NON_VALUE: Option<Self::T> = Some(Date::INVALID);
     const ID: u8 = 0xe1;
+    const CLASS: DataClass = DataClass::Analog;
+
+    fn is_non_value(&self) -> bool {
+        *self == Self::INVALID
+    }
 }
 impl ZclCompatibleType for Date {
     fn len(&self) -> usize {
         0x4
     }
 }
+impl ZclEncode for Date {
+    fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+        [self.year, self.month, self.day_of_month, self.day_of_week].write_le(data, 0x4)
+    }
+}
+impl ZclDecode<'_> for Date {
+    fn from_bytes(data: &[u8]) -> Result<Self, ZclError> {
+        let [year, month, day_of_month, day_of_week] = Primitive::read_le(data, 0x4)?;
+        Ok(Self {
+            year,
+            month,
+            day_of_month,
+            day_of_week,
+        })
+    }
+}
+
+basic_type!(UtcTime, u32, Some(u32::MAX), 0xe2, 0x4, Analog);
+basic_type!(ClusterId, u16, Some(u16::MAX), 0xe8, 0x2, Discrete);
+basic_type!(AttributeId, u16, Some(u16::MAX), 0xe9, 0x2, Discrete);
+basic_type!(BacnetOid, u32, Some(u32::MAX), 0xea, 0x4, Discrete);
+basic_type!(IeeeAddress, u64, Some(u64::MAX), 0xf0, 0x8, Discrete);
+basic_type!(SecurityKey, [u8; 16], None, 0xf1, 0x10, Discrete);
+basic_type!(Unknown, (), None, 0xff, 0x0, Null);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Array, Bag, Members, Set, Structure, ZclValue};
+
+    /// Checks that the static class of every type agrees with the lookup by type ID
+    macro_rules! assert_classes {
+        ($($type: ty),* $(,)?) => {$(
+            assert_eq!(
+                data_class(<$type>::ID),
+                Some(<$type>::CLASS),
+                "{}",
+                stringify!($type)
+            );
+        )*};
+    }
+
+    #[test]
+    fn class_constants_match_data_class() {
+        assert_classes!(
+            NoData,
+            Data8,
+            Data16,
+            Data24,
+            Data32,
+            Data40,
+            Data48,
+            Data56,
+            Data64,
+            Bool,
+            Bitmap8<u8>,
+            Bitmap16<u16>,
+            Bitmap24<u32>,
+            Bitmap32<u32>,
+            Bitmap40<u64>,
+            Bitmap48<u64>,
+            Bitmap56<u64>,
+            Bitmap64<u64>,
+            U8,
+            U16,
+            U24,
+            U32,
+            U40,
+            U48,
+            U56,
+            U64,
+            I8,
+            I16,
+            I24,
+            I32,
+            I40,
+            I48,
+            I56,
+            I64,
+            Enum8<u8>,
+            Enum16<u16>,
+            SemiFloat,
+            F32,
+            F64,
+            OctetString,
+            CharacterString,
+            LongOctetString,
+            LongCharacterString,
+            LossyCharacterString,
+            LossyLongCharacterString,
+            TimeOfDay,
+            Date,
+            UtcTime,
+            ClusterId,
+            AttributeId,
+            BacnetOid,
+            IeeeAddress,
+            SecurityKey,
+            Unknown,
+            Array<ZclValue>,
+            Set<ZclValue>,
+            Bag<ZclValue>,
+            Structure<Members>,
+        );
+        // Reserved IDs have no class
+        assert_eq!(data_class(0x01), None);
+        assert_eq!(data_class(0x3b), None);
+    }
+
+    #[test]
+    fn enum_non_values() {
+        assert!(Enum8(0xffu8).is_non_value());
+        // 0xff is an ordinary value of a 16-bit enumeration
+        assert!(!Enum16(0x00ffu16).is_non_value());
+        assert!(Enum16(0xffffu16).is_non_value());
+        assert_eq!(Enum16::<u16>::from_bytes(&[0xff, 0xff]), Ok(Enum16(0xffff)));
+        let mut buf = [0u8; 2];
+        assert_eq!(Enum16(0x1234u16).to_bytes(&mut buf), Ok(2));
+        assert_eq!(buf, [0x34, 0x12]);
+    }
+
+    /// The value of a finite semi-precision bit pattern, computed independently of
+    /// [`SemiFloat::to_f32`]. Powers of two and 11 bit significands are exact in f32.
+    fn reference(bits: u16) -> f32 {
+        let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+        let exp = ((bits >> 10) & 0x1f) as u32;
+        let mant = (bits & 0x03ff) as f32;
+        let magnitude = match exp {
+            0 => mant * f32::from_bits((127 - 24) << 23),
+            _ => (1.0 + mant / 1024.0) * f32::from_bits((exp + 127 - 15) << 23),
+        };
+        sign * magnitude
+    }
+
+    #[test]
+    fn semi_float_all_bit_patterns() {
+        for bits in 0..=u16::MAX {
+            let semi = SemiFloat(bits);
+            let value = semi.to_f32();
+            if bits & 0x7c00 == 0x7c00 && bits & 0x03ff != 0 {
+                assert!(semi.is_nan() && value.is_nan(), "{bits:#06x}");
+                // Signalling NaNs are quieted, the payload is preserved
+                assert_eq!(SemiFloat::from_f32(value).0, bits | 0x0200, "{bits:#06x}");
+                continue;
+            }
+            assert!(!semi.is_nan(), "{bits:#06x}");
+            if bits & 0x7fff == 0x7c00 {
+                assert!(value.is_infinite());
+                assert_eq!(value.is_sign_negative(), bits & 0x8000 != 0);
+            } else {
+                assert_eq!(value.to_bits(), reference(bits).to_bits(), "{bits:#06x}");
+            }
+            assert_eq!(SemiFloat::from_f32(value).0, bits, "{bits:#06x}");
+        }
+    }
+
+    #[test]
+    fn semi_float_rounds_to_nearest_even() {
+        // Every midpoint between two adjacent finite values, and its neighbours
+        for bits in 0..0x7bffu16 {
+            let (low, high) = (SemiFloat(bits).to_f32(), SemiFloat(bits + 1).to_f32());
+            let mid = (low + high) / 2.0;
+            let even = if bits & 1 == 0 { bits } else { bits + 1 };
+            assert_eq!(SemiFloat::from_f32(mid).0, even, "{bits:#06x}");
+            assert_eq!(SemiFloat::from_f32(-mid).0, even | 0x8000, "{bits:#06x}");
+            let below = f32::from_bits(mid.to_bits() - 1);
+            let above = f32::from_bits(mid.to_bits() + 1);
+            assert_eq!(SemiFloat::from_f32(below).0, bits, "{bits:#06x}");
+            assert_eq!(SemiFloat::from_f32(above).0, bits + 1, "{bits:#06x}");
+        }
+        assert_eq!(SemiFloat::from_f32(1.0 + 1.0 / 2048.0).0, 0x3c00);
+        assert_eq!(SemiFloat::from_f32(1.0 + 3.0 / 2048.0).0, 0x3c02);
+        // The largest subnormal rounds up into the smallest normal number
+        assert_eq!(SemiFloat::from_f32(1023.5 / 16_777_216.0).0, 0x0400);
+    }
 
-basic_type!(UtcTime, u32, Some(u32::MAX), 0xe2, 0x4);
-basic_type!(ClusterId, u16, Some(u16::MAX), 0xe8, 0x2);
-basic_type!(AttributeId, u16, Some(u16::MAX), 0xe9, 0x2);
-basic_type!(BacnetOid, u32, Some(u32::MAX), 0xea, 0x4);
-basic_type!(IeeeAddress, u64, Some(u64::MAX), 0xf0, 0x8);
-basic_type!(SecurityKey, [u8; 16], None, 0xf1, 0x10);
-basic_type!(Unknown, (), None, 0xff, 0x0);
+    #[test]
+    fn semi_float_overflow_and_underflow() {
+        assert_eq!(SemiFloat::from_f32(65504.0).0, 0x7bff);
+        assert_eq!(SemiFloat::from_f32(65519.996).0, 0x7bff);
+        // The midpoint to the next power of two rounds to even, which is infinity
+        assert_eq!(SemiFloat::from_f32(65520.0).0, 0x7c00);
+        assert_eq!(SemiFloat::from_f32(-1.0e10).0, 0xfc00);
+        assert_eq!(SemiFloat::from_f32(f32::INFINITY), SemiFloat::INFINITY);
+        assert_eq!(
+            SemiFloat::from_f32(f32::NEG_INFINITY),
+            SemiFloat::NEG_INFINITY
+        );
+        assert_eq!(SemiFloat::from_f32(f32::MAX).0, 0x7c00);
+        // Half of the smallest subnormal is a tie and rounds to zero, anything above to it
+        let min = 1.0 / 16_777_216.0;
+        assert_eq!(SemiFloat::from_f32(min).0, 0x0001);
+        assert_eq!(SemiFloat::from_f32(min / 2.0).0, 0x0000);
+        assert_eq!(SemiFloat::from_f32(min * 0.5000001).0, 0x0001);
+        assert_eq!(SemiFloat::from_f32(-min / 4.0).0, 0x8000);
+        assert_eq!(SemiFloat::from_f32(f32::MIN_POSITIVE).0, 0x0000);
+        assert_eq!(SemiFloat::from_f32(f32::from_bits(1)).0, 0x0000);
+        assert!(SemiFloat::from_f32(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn semi_float_wire_format() {
+        let mut buf = [0u8; 2];
+        assert_eq!(SemiFloat::from_f32(-2.5).to_bytes(&mut buf), Ok(2));
+        assert_eq!(buf, [0x00, 0xc1]);
+        assert_eq!(SemiFloat::from_bytes(&buf).unwrap().to_f32(), -2.5);
+        assert!(SemiFloat::NAN.is_non_value());
+        assert_eq!(
+            SemiFloat(0x3c00).compare(&SemiFloat(0x4000)),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn strings_round_trip() {
+        let mut buf = [0u8; 8];
+        let value = CharacterString(Some("abc"));
+        assert_eq!(value.to_bytes(&mut buf), Ok(4));
+        assert_eq!(buf[..4], [3, b'a', b'b', b'c']);
+        assert_eq!(CharacterString::from_bytes(&buf[..4]), Ok(value));
+
+        let value = LongOctetString(Some(&[0xde, 0xad][..]));
+        assert_eq!(value.len(), 4);
+        assert_eq!(value.to_bytes(&mut buf), Ok(4));
+        assert_eq!(buf[..4], [2, 0, 0xde, 0xad]);
+        assert_eq!(LongOctetString::from_bytes(&buf[..4]), Ok(value));
+
+        assert_eq!(OctetString::from_bytes(&[0xff]), Ok(OctetString(None)));
+        assert_eq!(LongCharacterString(None).to_bytes(&mut buf), Ok(2));
+        assert_eq!(buf[..2], [0xff, 0xff]);
+    }
+
+    #[test]
+    fn invalid_strings() {
+        assert_eq!(
+            CharacterString::from_bytes(&[2, b'a']),
+            Err(ZclError::Serialization)
+        );
+        assert_eq!(
+            CharacterString::from_bytes(&[1, 0xe9]),
+            Err(ZclError::InvalidUtf8)
+        );
+        let long = [0u8; 255];
+        assert_eq!(
+            OctetString(Some(&long[..])).to_bytes(&mut [0u8; 256]),
+            Err(ZclError::ValueOutOfRange)
+        );
+        assert_eq!(
+            OctetString(Some(&long[..254])).to_bytes(&mut [0u8; 255]),
+            Ok(255)
+        );
+    }
+
+    #[test]
+    fn lossy_strings() {
+        let value = LossyCharacterString::from_bytes(&[4, b'I', 0xe9, b'A', 0xff]).unwrap();
+        assert_eq!(value.as_str(), None);
+        assert!(value.chars().eq(['I', '\u{e9}', 'A', '\u{ff}']));
+        let value = LossyLongCharacterString::from_bytes(&[3, 0, 0xc3, 0xa9, b'!']).unwrap();
+        assert_eq!(value.as_str(), Some("\u{e9}!"));
+    }
+}
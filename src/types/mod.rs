@@ -1,5 +1,10 @@
 mod attribute;
+pub(crate) mod codec;
+mod collection;
 mod datatypes;
+mod value;
 
 pub use attribute::*;
+pub use collection::*;
 pub use datatypes::*;
+pub use value::*;
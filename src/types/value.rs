@@ -0,0 +1,391 @@
+use crate::ZclError;
+use crate::types::codec;
+use crate::types::*;
+
+macro_rules! zcl_value {
+    (
+        $($variant: ident($type: ty),)*
+        $(#[raw] $raw_variant: ident($raw_type: ty),)*
+        $(#[lossy] $lossy_variant: ident($lossy_type: ty),)*
+    ) => {
+        /// A dynamically typed attribute value, e.g. as received in a Read Attributes
+        /// Response or Report Attributes command where the data type is only known from
+        /// the type ID transmitted alongside the value.
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub enum ZclValue<'a> {
+            $($variant($type),)*
+            $($raw_variant($raw_type),)*
+            $($lossy_variant($lossy_type),)*
+        }
+
+        impl<'a> ZclValue<'a> {
+            /// Decodes a value of the data type identified by `type_id` from the start of `data`.
+            pub fn decode(type_id: u8, data: &'a [u8]) -> Result<Self, ZclError> {
+                match type_id {
+                    $(<$type>::ID => Ok(Self::$variant(<$type>::from_bytes(data)?)),)*
+                    $(<$raw_type>::ID => Ok(Self::$raw_variant(<$raw_type>::from_bytes(data)?)),)*
+                    _ => Err(ZclError::UnknownDataType(type_id)),
+                }
+            }
+
+            /// Like [`Self::decode`], but character strings which are not valid UTF-8 are
+            /// decoded as [`LossyCharacterString`] or [`LossyLongCharacterString`] instead of
+            /// failing. Some devices send e.g. Latin-1 encoded manufacturer names.
+            pub fn decode_lossy(type_id: u8, data: &'a [u8]) -> Result<Self, ZclError> {
+                match Self::decode(type_id, data) {
+                    Err(ZclError::InvalidUtf8) if type_id == CharacterString::ID => {
+                        Ok(Self::LossyCharacterString(LossyCharacterString::from_bytes(data)?))
+                    }
+                    Err(ZclError::InvalidUtf8) if type_id == LongCharacterString::ID => {
+                        Ok(Self::LossyLongCharacterString(LossyLongCharacterString::from_bytes(data)?))
+                    }
+                    result => result,
+                }
+            }
+
+            /// Encodes the value without its type ID and returns the number of bytes written.
+            pub fn encode(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                match self {
+                    $(Self::$variant(x) => x.to_bytes(data),)*
+                    $(Self::$raw_variant(x) => x.to_bytes(data),)*
+                    $(Self::$lossy_variant(x) => x.to_bytes(data),)*
+                }
+            }
+
+            /// The data type ID of the contained value
+            pub fn type_id(&self) -> u8 {
+                match self {
+                    $(Self::$variant(_) => <$type>::ID,)*
+                    $(Self::$raw_variant(_) => <$raw_type>::ID,)*
+                    $(Self::$lossy_variant(_) => <$lossy_type>::ID,)*
+                }
+            }
+        }
+
+        impl ZclCompatibleType for ZclValue<'_> {
+            fn len(&self) -> usize {
+                match self {
+                    $(Self::$variant(x) => x.len(),)*
+                    $(Self::$raw_variant(x) => x.len(),)*
+                    $(Self::$lossy_variant(x) => x.len(),)*
+                }
+            }
+        }
+
+        impl ZclEncode for ZclValue<'_> {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ZclError> {
+                self.encode(data)
+            }
+        }
+
+        $(
+            impl<'a> From<$type> for ZclValue<'a> {
+                fn from(value: $type) -> Self {
+                    Self::$variant(value)
+                }
+            }
+
+            impl<'a> TryFrom<ZclValue<'a>> for $type {
+                type Error = ZclError;
+                fn try_from(value: ZclValue<'a>) -> Result<Self, ZclError> {
+                    match value {
+                        ZclValue::$variant(x) => Ok(x),
+                        _ => Err(ZclError::TypeMismatch),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+zcl_value! {
+    NoData(NoData),
+    Data8(Data8),
+    Data16(Data16),
+    Data24(Data24),
+    Data32(Data32),
+    Data40(Data40),
+    Data48(Data48),
+    Data56(Data56),
+    Data64(Data64),
+    Bool(Bool),
+    U8(U8),
+    U16(U16),
+    U24(U24),
+    U32(U32),
+    U40(U40),
+    U48(U48),
+    U56(U56),
+    U64(U64),
+    I8(I8),
+    I16(I16),
+    I24(I24),
+    I32(I32),
+    I40(I40),
+    I48(I48),
+    I56(I56),
+    I64(I64),
+    SemiFloat(SemiFloat),
+    F32(F32),
+    F64(F64),
+    OctetString(OctetString<'a>),
+    CharacterString(CharacterString<'a>),
+    LongOctetString(LongOctetString<'a>),
+    LongCharacterString(LongCharacterString<'a>),
+    Array(Array<'a, ZclValue<'a>>),
+    Structure(Structure<Members<'a>>),
+    Set(Set<'a, ZclValue<'a>>),
+    Bag(Bag<'a, ZclValue<'a>>),
+    TimeOfDay(TimeOfDay),
+    Date(Date),
+    UtcTime(UtcTime),
+    ClusterId(ClusterId),
+    AttributeId(AttributeId),
+    BacnetOid(BacnetOid),
+    IeeeAddress(IeeeAddress),
+    SecurityKey(SecurityKey),
+    Unknown(Unknown),
+    // Enumerations and bitmaps convert through their raw value, see the generic impls below
+    #[raw] Enum8(Enum8<u8>),
+    #[raw] Enum16(Enum16<u16>),
+    #[raw] Bitmap8(Bitmap8<u8>),
+    #[raw] Bitmap16(Bitmap16<u16>),
+    #[raw] Bitmap24(Bitmap24<u32>),
+    #[raw] Bitmap32(Bitmap32<u32>),
+    #[raw] Bitmap40(Bitmap40<u64>),
+    #[raw] Bitmap48(Bitmap48<u64>),
+    #[raw] Bitmap56(Bitmap56<u64>),
+    #[raw] Bitmap64(Bitmap64<u64>),
+    // Only produced by decode_lossy, the strict variants are used for valid UTF-8
+    #[lossy] LossyCharacterString(LossyCharacterString<'a>),
+    #[lossy] LossyLongCharacterString(LossyLongCharacterString<'a>),
+}
+
+impl ZclValue<'_> {
+    /// The length of the value of the data type identified by `type_id` at the start of
+    /// `data`, without decoding it. This allows skipping values which are invalid, e.g.
+    /// out of range, as long as their data type is known.
+    pub fn encoded_len(type_id: u8, data: &[u8]) -> Result<usize, ZclError> {
+        let len = match type_id {
+            0x00 | 0xff => 0,
+            0x10 | 0x30 => 1,
+            0x31 | 0x38 | 0xe8 | 0xe9 => 2,
+            0x39 | 0xe0 | 0xe1 | 0xe2 | 0xea => 4,
+            0x3a | 0xf0 => 8,
+            0xf1 => 16,
+            0x08..=0x0f => (type_id - 0x07) as usize,
+            0x18..=0x1f => (type_id - 0x17) as usize,
+            0x20..=0x27 => (type_id - 0x1f) as usize,
+            0x28..=0x2f => (type_id - 0x27) as usize,
+            0x41 | 0x42 => prefixed_len(data, 1)?,
+            0x43 | 0x44 => prefixed_len(data, 2)?,
+            // Array, set and bag
+            0x48 | 0x50 | 0x51 => {
+                let element_type = codec::read_uint(data, 1)? as u8;
+                let count = codec::read_uint(&data[1..], 2)? as u16;
+                let mut offset = 3;
+                for _ in 0..count % 0xffff {
+                    offset += Self::encoded_len(element_type, &data[offset..])?;
+                }
+                offset
+            }
+            // Structure, every member carries its own type ID
+            0x4c => {
+                let count = codec::read_uint(data, 2)? as u16;
+                let mut offset = 2;
+                for _ in 0..count % 0xffff {
+                    let member_type = codec::read_uint(&data[offset..], 1)? as u8;
+                    offset += 1 + Self::encoded_len(member_type, &data[offset + 1..])?;
+                }
+                offset
+            }
+            _ => return Err(ZclError::UnknownDataType(type_id)),
+        };
+        match len <= data.len() {
+            true => Ok(len),
+            false => Err(ZclError::Serialization),
+        }
+    }
+}
+
+/// The length of a string including its `width` byte length prefix
+fn prefixed_len(data: &[u8], width: usize) -> Result<usize, ZclError> {
+    Ok(width + codec::read_prefixed(data, width)?.map_or(0, <[u8]>::len))
+}
+
+// The lossy strings share their type ID with the strict ones, so they also
+// convert from values which were decoded as valid UTF-8.
+
+macro_rules! lossy_value {
+    ($($name: ident($strict: ident)),*) => {$(
+        impl<'a> From<$name<'a>> for ZclValue<'a> {
+            fn from(value: $name<'a>) -> Self {
+                Self::$name(value)
+            }
+        }
+
+        impl<'a> TryFrom<ZclValue<'a>> for $name<'a> {
+            type Error = ZclError;
+            fn try_from(value: ZclValue<'a>) -> Result<Self, ZclError> {
+                match value {
+                    ZclValue::$name(x) => Ok(x),
+                    ZclValue::$strict(x) => Ok(Self(x.0.map(str::as_bytes))),
+                    _ => Err(ZclError::TypeMismatch),
+                }
+            }
+        }
+    )*};
+}
+
+lossy_value!(
+    LossyCharacterString(CharacterString),
+    LossyLongCharacterString(LongCharacterString)
+);
+
+// Enumerations and bitmaps are carried by their raw value, the typed wrappers
+// convert from and to it for any generated enumeration or bitmap.
+
+impl<T: ZclEnum<Repr = u8>> From<Enum8<T>> for ZclValue<'_> {
+    fn from(value: Enum8<T>) -> Self {
+        Self::Enum8(Enum8(value.0.into_raw()))
+    }
+}
+
+impl<T: ZclEnum<Repr = u8>> TryFrom<ZclValue<'_>> for Enum8<T> {
+    type Error = ZclError;
+    fn try_from(value: ZclValue<'_>) -> Result<Self, ZclError> {
+        match value {
+            ZclValue::Enum8(x) => T::try_from_raw(x.0)
+                .map(Self)
+                .ok_or(ZclError::ValueOutOfRange),
+            _ => Err(ZclError::TypeMismatch),
+        }
+    }
+}
+
+impl<T: ZclEnum<Repr = u16>> From<Enum16<T>> for ZclValue<'_> {
+    fn from(value: Enum16<T>) -> Self {
+        Self::Enum16(Enum16(value.0.into_raw()))
+    }
+}
+
+impl<T: ZclEnum<Repr = u16>> TryFrom<ZclValue<'_>> for Enum16<T> {
+    type Error = ZclError;
+    fn try_from(value: ZclValue<'_>) -> Result<Self, ZclError> {
+        match value {
+            ZclValue::Enum16(x) => T::try_from_raw(x.0)
+                .map(Self)
+                .ok_or(ZclError::ValueOutOfRange),
+            _ => Err(ZclError::TypeMismatch),
+        }
+    }
+}
+
+macro_rules! bitmap_value {
+    ($($name: ident($repr: ty)),*) => {$(
+        impl<T: ZclBitmap<Repr = $repr>> From<$name<T>> for ZclValue<'_> {
+            fn from(value: $name<T>) -> Self {
+                Self::$name($name(value.0.into_raw()))
+            }
+        }
+
+        impl<T: ZclBitmap<Repr = $repr>> TryFrom<ZclValue<'_>> for $name<T> {
+            type Error = ZclError;
+            fn try_from(value: ZclValue<'_>) -> Result<Self, ZclError> {
+                match value {
+                    ZclValue::$name(x) => Ok(Self(T::from_raw(x.0))),
+                    _ => Err(ZclError::TypeMismatch),
+                }
+            }
+        }
+    )*};
+}
+
+bitmap_value!(
+    Bitmap8(u8),
+    Bitmap16(u16),
+    Bitmap24(u32),
+    Bitmap32(u32),
+    Bitmap40(u64),
+    Bitmap48(u64),
+    Bitmap56(u64),
+    Bitmap64(u64)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_lossy_keeps_invalid_utf8() {
+        let data = [3, b'I', 0xe9, b'A'];
+        assert_eq!(ZclValue::decode(0x42, &data), Err(ZclError::InvalidUtf8));
+        let value = ZclValue::decode_lossy(0x42, &data).unwrap();
+        assert_eq!(
+            value,
+            ZclValue::LossyCharacterString(LossyCharacterString(Some(&data[1..])))
+        );
+        assert_eq!(value.type_id(), 0x42);
+        assert_eq!(value.len(), 4);
+        let mut buf = [0u8; 4];
+        assert_eq!(value.encode(&mut buf), Ok(4));
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    fn encoded_len_matches_decoded_values() {
+        let payloads: [&[u8]; 3] = [
+            // A string of one byte, also an array of two uint8
+            &[0x20, 2, 0, 5, 6],
+            // A structure with an uint8 and a string member
+            &[2, 0, 0x20, 5, 0x42, 1, b'a'],
+            // A set of one octet string
+            &[0x41, 1, 0, 2, 0xaa, 0xbb],
+        ];
+        for payload in payloads {
+            let mut data = [0u8; 40];
+            data[..payload.len()].copy_from_slice(payload);
+            for type_id in 0..=0xff {
+                let len = ZclValue::encoded_len(type_id, &data);
+                match ZclValue::decode(type_id, &data) {
+                    Ok(value) => assert_eq!(len, Ok(value.len()), "type 0x{type_id:02x}"),
+                    Err(error @ (ZclError::UnknownDataType(_) | ZclError::Serialization)) => {
+                        assert_eq!(len, Err(error), "type 0x{type_id:02x}")
+                    }
+                    Err(_) => assert!(len.is_ok(), "type 0x{type_id:02x}"),
+                }
+            }
+        }
+        assert_eq!(ZclValue::encoded_len(0x48, &[0x20, 2, 0, 5, 6]), Ok(5));
+        assert_eq!(
+            ZclValue::encoded_len(0x4c, &[2, 0, 0x20, 5, 0x42, 1, b'a']),
+            Ok(7)
+        );
+        // Invalid values still have a length
+        assert_eq!(ZclValue::encoded_len(0x10, &[0x02]), Ok(1));
+        assert_eq!(
+            ZclValue::encoded_len(0x48, &[0x10, 2, 0, 0x02, 0x03]),
+            Ok(5)
+        );
+        assert_eq!(
+            ZclValue::encoded_len(0x48, &[0x10, 3, 0, 0x02, 0x03]),
+            Err(ZclError::Serialization)
+        );
+    }
+
+    #[test]
+    fn decode_lossy_prefers_strict_strings() {
+        let data = [2, 0, b'o', b'k'];
+        assert_eq!(
+            ZclValue::decode_lossy(0x44, &data),
+            Ok(ZclValue::LongCharacterString(LongCharacterString(Some(
+                "ok"
+            ))))
+        );
+        let value = ZclValue::CharacterString(CharacterString(Some("ok")));
+        assert_eq!(
+            LossyCharacterString::try_from(value),
+            Ok(LossyCharacterString(Some(b"ok".as_slice())))
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// An error in a cluster definition file
+#[derive(Error, Debug)]
+#[error("{}:{line}:{column}: {kind}", file.display())]
+pub struct Error {
+    pub file: PathBuf,
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// The column of the error, starting at 1
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Error, Debug)]
+pub enum ErrorKind {
+    #[error("failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unexpected line '{0}'")]
+    UnexpectedLine(String),
+    #[error("expected {expected} fields but found {found}")]
+    FieldCount { expected: usize, found: usize },
+    #[error("expected {expected} but found '{found}'")]
+    Expected {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("invalid number '{0}'")]
+    InvalidNumber(String),
+    #[error("'{0}' is not a valid name")]
+    InvalidName(String),
+    #[error("invalid value '{0}'")]
+    InvalidValue(String),
+    #[error("unknown type '{0}'")]
+    UnknownType(String),
+    #[error("duplicate attribute ID {0}")]
+    DuplicateAttribute(String),
+    #[error("duplicate command {0}")]
+    DuplicateCommand(String),
+    #[error("value {value} does not fit into {width} bits")]
+    Overflow { value: String, width: u32 },
+    #[error("required field '{0}' follows an optional field")]
+    RequiredAfterOptional(String),
+    #[error("duplicate name '{0}'")]
+    DuplicateName(String),
+    #[error("duplicate enum value {0}")]
+    DuplicateEnumValue(String),
+    #[error("bits {0} overlap a bit which is already defined")]
+    OverlappingBits(String),
+    #[error("'}}' without an open block")]
+    UnmatchedBrace,
+    #[error("block is never closed")]
+    UnclosedBlock,
+    #[error("range refers to undefined attribute '{0}'")]
+    UndefinedReference(String),
+    #[error("unknown cluster '{0}'")]
+    UnknownCluster(String),
+    #[error("variant must have the ID {0} of its base cluster")]
+    VariantId(String),
+}
+
+/// A position in a cluster definition file
+#[derive(Clone, Debug)]
+pub(crate) struct Location {
+    pub(crate) file: PathBuf,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Location {
+    pub(crate) fn new(file: &Path, line: usize, column: usize) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            line,
+            column,
+        }
+    }
+
+    pub(crate) fn error(&self, kind: ErrorKind) -> Error {
+        Error {
+            file: self.file.clone(),
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
@@ -0,0 +1,2351 @@
+//! Code generator turning cluster definition files into the attribute, cluster and
+//! enum definitions of the `zcl` crate. Crates defining their own clusters can use it
+//! from their build script:
+//!
+//! ```no_run
+//! let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//! let tokens = zcl_codegen::generate(["clusters/vendor.txt"]).unwrap_or_else(|e| panic!("{e}"));
+//! std::fs::write(out.join("clusters.rs"), zcl_codegen::format(tokens)).unwrap();
+//! ```
+
+mod error;
+
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use syn::{Expr, Ident, Lifetime, Lit, LitInt, Type, parse_quote};
+
+use error::Location;
+pub use error::{Error, ErrorKind};
+
+#[derive(Clone)]
+struct Attribute {
+    id: String,
+    name: String,
+    spec_type: String,
+    rust_type: TokenStream,
+    rust_type_doc: String,
+    range: String,
+    access: String,
+    default: String,
+    mandatory: String,
+    /// `Server`, `Client` or `Either`, naming the `AttributeSide` variant
+    side: &'static str,
+    manufacturer_code: Option<String>,
+    location: Location,
+    type_location: Location,
+    range_location: Location,
+}
+
+impl Attribute {
+    /// Whether both attributes have the same ID, side and manufacturer code
+    fn same_id(&self, other: &Attribute) -> bool {
+        parse_id(&self.id) == parse_id(&other.id)
+            && self.side == other.side
+            && self.manufacturer_code == other.manufacturer_code
+    }
+}
+
+#[derive(Clone)]
+struct EnumVariant {
+    value: String,
+    name: String,
+}
+
+#[derive(Clone)]
+struct Enum {
+    repr_type: Type,
+    name: String,
+    /// The number of bits on the wire
+    width: u32,
+    variants: Vec<EnumVariant>,
+    location: Location,
+}
+
+impl Enum {
+    /// The all-ones value which marks an invalid value
+    fn non_value(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+}
+
+/// A single bit or a range of bits of a bitmap, `low..=high`
+#[derive(Clone)]
+struct BitField {
+    name: String,
+    low: u32,
+    high: u32,
+}
+
+#[derive(Clone)]
+struct Bitmap {
+    name: String,
+    /// The number of bits on the wire
+    width: u32,
+    fields: Vec<BitField>,
+    location: Location,
+}
+
+impl Bitmap {
+    /// The integer type holding the bits
+    fn repr_type(&self) -> Type {
+        match self.width {
+            8 => parse_quote!(u8),
+            16 => parse_quote!(u16),
+            24 | 32 => parse_quote!(u32),
+            _ => parse_quote!(u64),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Field {
+    name: String,
+    spec_type: String,
+    rust_type: TokenStream,
+    /// Trailing fields added in later revisions of the specification, which older
+    /// devices leave out. They decode as zero if the payload ends before them.
+    optional: bool,
+    type_location: Location,
+}
+
+impl Field {
+    /// Whether the field borrows from the received payload
+    fn borrows(&self) -> bool {
+        is_sized(&self.spec_type)
+            || self.spec_type == "struct"
+            || ["array:", "set:", "bag:"]
+                .iter()
+                .any(|prefix| self.spec_type.starts_with(prefix))
+    }
+}
+
+#[derive(Clone)]
+struct Command {
+    id: String,
+    name: String,
+    /// `ClientToServer` for commands received by the server, `ServerToClient` for
+    /// those it generates, naming the `Direction` variant
+    direction: &'static str,
+    mandatory: String,
+    fields: Vec<Field>,
+    location: Location,
+}
+
+impl Command {
+    /// Whether both commands have the same ID and direction
+    fn same_id(&self, other: &Command) -> bool {
+        parse_id(&self.id) == parse_id(&other.id) && self.direction == other.direction
+    }
+
+    /// The direction as written in the definition file
+    fn direction_name(&self) -> &'static str {
+        match self.direction {
+            "ClientToServer" => "received",
+            _ => "generated",
+        }
+    }
+
+    /// Whether any field borrows from the received payload
+    fn borrows(&self) -> bool {
+        self.fields.iter().any(Field::borrows)
+    }
+}
+
+struct Cluster {
+    name: String,
+    id: String,
+    /// The cluster this one is a variant of, e.g. with different ranges or mandatory attributes
+    base: Option<String>,
+    /// The device types using this variant instead of its base cluster
+    device_types: Vec<String>,
+    manufacturer_code: Option<String>,
+    attributes: Vec<Attribute>,
+    enums: Vec<Enum>,
+    bitmaps: Vec<Bitmap>,
+    commands: Vec<Command>,
+    location: Location,
+}
+
+/// The rust type of a ZCL type, borrowing strings and collections for `lifetime`
+fn kind_to_type(ident: &str, kind: &str, lifetime: &Lifetime) -> Option<(TokenStream, String)> {
+    macro_rules! simple {
+        ($x:tt) => {
+            (
+                quote! { $x },
+                concat!("[`", stringify!($x), "`]").to_string(),
+            )
+        };
+    }
+    Some(match kind {
+        "nodata" => simple! { NoData },
+        "data8" => simple! { Data8 },
+        "data16" => simple! { Data16 },
+        "data24" => simple! { Data24 },
+        "data32" => simple! { Data32 },
+        "data40" => simple! { Data40 },
+        "data48" => simple! { Data48 },
+        "data56" => simple! { Data56 },
+        "data64" => simple! { Data64 },
+        "bool" => simple! { Bool },
+        "map8" => simple! { Bitmap8 },
+        "map16" => simple! { Bitmap16 },
+        "map24" => simple! { Bitmap24 },
+        "map32" => simple! { Bitmap32 },
+        "map40" => simple! { Bitmap40 },
+        "map48" => simple! { Bitmap48 },
+        "map56" => simple! { Bitmap56 },
+        "map64" => simple! { Bitmap64 },
+        "uint8" => simple! { U8 },
+        "uint16" => simple! { U16 },
+        "uint24" => simple! { U24 },
+        "uint32" => simple! { U32 },
+        "uint40" => simple! { U40 },
+        "uint48" => simple! { U48 },
+        "uint56" => simple! { U56 },
+        "uint64" => simple! { U64 },
+        "int8" => simple! { I8 },
+        "int16" => simple! { I16 },
+        "int24" => simple! { I24 },
+        "int32" => simple! { I32 },
+        "int40" => simple! { I40 },
+        "int48" => simple! { I48 },
+        "int56" => simple! { I56 },
+        "int64" => simple! { I64 },
+        x if bitmap_name(x).is_some() => {
+            let (map, name) = x.split_once(':').unwrap();
+            let (wrapper, _) = kind_to_type(ident, map, lifetime)?;
+            let name = format_ident!("{name}");
+            (
+                quote! { #wrapper::<#name> },
+                format!("[`{wrapper}`]::<[`{name}`]>"),
+            )
+        }
+        x if x.starts_with("enum8:") => {
+            let chosen_enum = format_ident!("{}", x.strip_prefix("enum8:").unwrap());
+            (
+                quote! { Enum8::<#chosen_enum> },
+                format!("[`Enum8`]::<[`{chosen_enum}`]>"),
+            )
+        }
+        x if x.starts_with("enum16:") => {
+            let chosen_enum = format_ident!("{}", x.strip_prefix("enum16:").unwrap());
+            (
+                quote! { Enum16::<#chosen_enum> },
+                format!("[`Enum16`]::<[`{chosen_enum}`]>"),
+            )
+        }
+        "enum8" => {
+            let ident = format_ident!("{ident}");
+            (
+                quote! { Enum8::<#ident> },
+                format!("[`Enum8`]::<[`{ident}`]>"),
+            )
+        }
+        "enum16" => {
+            let ident = format_ident!("{ident}");
+            (
+                quote! { Enum16::<#ident> },
+                format!("[`Enum16`]::<[`{ident}`]>"),
+            )
+        }
+        "semi" => simple! { SemiFloat },
+        "single" => simple! { F32 },
+        "double" => simple! { F64 },
+        "octstr" => (
+            quote! { OctetString::<#lifetime> },
+            "[`OctetString`]".to_string(),
+        ),
+        "string" => (
+            quote! { CharacterString::<#lifetime> },
+            "[`CharacterString`]".to_string(),
+        ),
+        "octstr16" => (
+            quote! { LongOctetString::<#lifetime> },
+            "[`LongOctetString`]".to_string(),
+        ),
+        "string16" => (
+            quote! { LongCharacterString::<#lifetime> },
+            "[`LongCharacterString`]".to_string(),
+        ),
+        // Character strings whose contents are not validated, e.g. binary data sent as
+        // a character string by some devices
+        "lossystring" => (
+            quote! { LossyCharacterString::<#lifetime> },
+            "[`LossyCharacterString`]".to_string(),
+        ),
+        "lossystring16" => (
+            quote! { LossyLongCharacterString::<#lifetime> },
+            "[`LossyLongCharacterString`]".to_string(),
+        ),
+        // "ASCII"     => quote! {  }}
+        x if x.starts_with("array:") || x.starts_with("set:") || x.starts_with("bag:") => {
+            let (collection, element) = x.split_once(':').unwrap();
+            let (element_type, element_doc) = kind_to_type(ident, element, lifetime)?;
+            let collection = format_ident!("{}", collection.to_case(Case::UpperCamel));
+            (
+                quote! { #collection::<#lifetime, #element_type> },
+                format!("[`{collection}`]::<{element_doc}>"),
+            )
+        }
+        "struct" => (
+            quote! { Structure::<Members<#lifetime>> },
+            "[`Structure`]::<[`Members`]>".to_string(),
+        ),
+        "ToD" => simple! { TimeOfDay },
+        "date" => simple! { Date },
+        "UTC" => simple! { UtcTime },
+        "clusterId" => simple! { ClusterId },
+        "attribId" => simple! { AttributeId },
+        "bacOID" => simple! { BacnetOid },
+        "EUI64" => simple! { IeeeAddress },
+        "key128" => simple! { SecurityKey },
+        "unk" => simple! { Unknown },
+        _ => return None,
+    })
+}
+
+/// Reinterprets a hexadecimal bound of a signed type as its two's complement value,
+/// e.g. 0x954d for an int16 becomes -27315.
+fn kind_to_literal(litv: &LitInt, kind: &str) -> TokenStream {
+    let val: i128 = litv
+        .base10_parse()
+        .expect("bounds are validated by the parser");
+    let bits = match kind.strip_prefix("int").map(str::parse::<u32>) {
+        Some(Ok(bits)) => bits,
+        _ => return quote! { #litv },
+    };
+    if val >= 1i128 << (bits - 1) {
+        let val = proc_macro2::Literal::i128_unsuffixed((1i128 << bits) - val);
+        quote! { -#val }
+    } else {
+        quote! { #litv }
+    }
+}
+
+/// The contents of a cluster definition file
+struct Definitions {
+    attributes: Vec<Attribute>,
+    clusters: Vec<Cluster>,
+    enums: Vec<Enum>,
+    bitmaps: Vec<Bitmap>,
+    /// Attributes, enums and bitmaps added to clusters which are defined elsewhere
+    extensions: Vec<Cluster>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Block {
+    Manufacturer,
+    Cluster,
+    Extension,
+    Client,
+    Enum,
+    Bitmap,
+    Command,
+}
+
+/// A line split into whitespace separated fields
+struct Line<'a> {
+    file: &'a Path,
+    number: usize,
+    fields: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Line<'a> {
+    fn new(file: &'a Path, number: usize, line: &'a str) -> Self {
+        let mut fields = Vec::new();
+        let mut start = None;
+        for (i, c) in line.char_indices() {
+            match (c.is_whitespace(), start) {
+                (true, Some(s)) => {
+                    fields.push((s + 1, &line[s..i]));
+                    start = None;
+                }
+                (false, None) => start = Some(i),
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            fields.push((s + 1, &line[s..]));
+        }
+        Self {
+            file,
+            number,
+            fields,
+        }
+    }
+
+    fn field(&self, i: usize) -> &'a str {
+        self.fields[i].1
+    }
+
+    fn location(&self, i: usize) -> Location {
+        let column = self.fields.get(i).or(self.fields.last()).map_or(1, |x| x.0);
+        Location::new(self.file, self.number, column)
+    }
+
+    fn error(&self, i: usize, kind: ErrorKind) -> Error {
+        self.location(i).error(kind)
+    }
+
+    fn expect_len(&self, expected: usize) -> Result<(), Error> {
+        match self.fields.len() {
+            found if found == expected => Ok(()),
+            found => Err(self.error(0, ErrorKind::FieldCount { expected, found })),
+        }
+    }
+
+    /// Checks a block header like `cluster Name 0x0000 {`
+    fn expect_header(&self, expected: usize) -> Result<(), Error> {
+        self.expect_len(expected)?;
+        match self.field(expected - 1) {
+            "{" => Ok(()),
+            found => Err(self.error(
+                expected - 1,
+                ErrorKind::Expected {
+                    expected: "'{'",
+                    found: found.to_string(),
+                },
+            )),
+        }
+    }
+
+    /// A name which is turned into a rust identifier
+    fn name(&self, i: usize) -> Result<String, Error> {
+        let name = self.field(i);
+        match syn::parse_str::<Ident>(name) {
+            Ok(_) => Ok(name.to_string()),
+            Err(_) => Err(self.error(i, ErrorKind::InvalidName(name.to_string()))),
+        }
+    }
+
+    /// A 16 bit ID like an attribute ID or manufacturer code
+    fn id(&self, i: usize) -> Result<String, Error> {
+        let id = self.field(i);
+        match parse_number(id) {
+            Some(x) if x <= u16::MAX as u64 => Ok(id.to_string()),
+            _ => Err(self.error(i, ErrorKind::InvalidNumber(id.to_string()))),
+        }
+    }
+
+    /// An 8 bit command ID
+    fn command_id(&self, i: usize) -> Result<String, Error> {
+        let id = self.field(i);
+        match parse_number(id) {
+            Some(x) if x <= u8::MAX as u64 => Ok(id.to_string()),
+            _ => Err(self.error(i, ErrorKind::InvalidNumber(id.to_string()))),
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Option<u64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn parse_id(id: &str) -> u16 {
+    parse_number(id).expect("ids are validated by the parser") as u16
+}
+
+/// The flags type of a `map8:Name` bitmap, `None` for plain bitmaps
+fn bitmap_name(spec_type: &str) -> Option<&str> {
+    spec_type
+        .split_once(':')
+        .filter(|(kind, _)| kind.starts_with("map"))
+        .map(|(_, name)| name)
+}
+
+fn is_sized(spec_type: &str) -> bool {
+    matches!(
+        spec_type,
+        "octstr" | "string" | "octstr16" | "string16" | "lossystring" | "lossystring16"
+    )
+}
+
+/// The width in bits of an integer, enumeration, bitmap or discrete data type and
+/// whether it is signed, `None` for other types
+fn integer_width(spec_type: &str) -> Option<(u32, bool)> {
+    let kind = spec_type.split(':').next().unwrap();
+    let (width, signed) = match kind.strip_prefix("int") {
+        Some(width) => (width, true),
+        None => ["uint", "enum", "map", "data"]
+            .iter()
+            .find_map(|x| kind.strip_prefix(x))
+            .map(|width| (width, false))?,
+    };
+    Some((width.parse().ok()?, signed))
+}
+
+/// The boundaries of a `min,max` range
+fn bounds(attr: &Attribute) -> Option<(&str, &str)> {
+    match attr.range.as_str() {
+        "value" | "full-non" | "-" | "full" => None,
+        _ if is_sized(&attr.spec_type) => None,
+        range => range.split_once(','),
+    }
+}
+
+fn parse_attribute(
+    line: &Line,
+    side: &'static str,
+    manufacturer_code: &Option<String>,
+) -> Result<Attribute, Error> {
+    line.expect_len(8)?;
+    let id = line.id(1)?;
+    let name = line.name(2)?;
+    let spec_type = line.field(3);
+    let (rust_type, rust_type_doc) = kind_to_type(&name, spec_type, &parse_quote!('static))
+        .ok_or_else(|| line.error(3, ErrorKind::UnknownType(spec_type.to_string())))?;
+    let range = match (spec_type.split(':').next().unwrap(), line.field(4)) {
+        ("enum8", "0x00,0xff") => "full-non",
+        ("enum16", "0x0000,0xffff") => "full-non",
+        (_, x) => x,
+    };
+    let invalid = |i: usize| line.error(i, ErrorKind::InvalidValue(line.field(i).to_string()));
+
+    let valid_range = match range {
+        "value" | "full-non" | "-" | "full" => true,
+        size if is_sized(spec_type) => parse_number(size).is_some(),
+        range => range.split_once(',').is_some_and(|(min, max)| {
+            [min, max].iter().all(|bound| {
+                syn::parse_str::<LitInt>(bound).is_ok() || syn::parse_str::<Ident>(bound).is_ok()
+            })
+        }),
+    };
+    if !valid_range {
+        return Err(invalid(4));
+    }
+    if let (Some((bits, signed)), Some((min, max))) =
+        (integer_width(spec_type), range.split_once(','))
+    {
+        for bound in [min, max] {
+            let Ok(lit) = syn::parse_str::<LitInt>(bound) else {
+                continue;
+            };
+            let Ok(value) = lit.base10_parse::<i128>() else {
+                return Err(line.error(4, ErrorKind::InvalidNumber(bound.to_string())));
+            };
+            // Signed bounds may also be given as their two's complement, see kind_to_literal
+            let lowest = if signed { -(1i128 << (bits - 1)) } else { 0 };
+            if value < lowest || value >= 1i128 << bits {
+                return Err(line.error(
+                    4,
+                    ErrorKind::Overflow {
+                        value: bound.to_string(),
+                        width: bits,
+                    },
+                ));
+            }
+        }
+    }
+    let default = line.field(6);
+    if !matches!(default, "-" | "non") && syn::parse_str::<Expr>(default).is_err() {
+        return Err(invalid(6));
+    }
+    let mandatory = line.field(7);
+    if !matches!(mandatory, "M" | "O") {
+        return Err(invalid(7));
+    }
+
+    Ok(Attribute {
+        id,
+        name,
+        spec_type: spec_type.to_string(),
+        rust_type,
+        rust_type_doc,
+        range: range.to_string(),
+        access: line.field(5).to_string(),
+        default: default.to_string(),
+        mandatory: mandatory.to_string(),
+        side,
+        manufacturer_code: manufacturer_code.clone(),
+        location: line.location(1),
+        type_location: line.location(3),
+        range_location: line.location(4),
+    })
+}
+
+/// Adds an attribute, rejecting a second definition with the same ID, side and manufacturer code
+fn push_attribute(attributes: &mut Vec<Attribute>, attr: Attribute) -> Result<(), Error> {
+    if attributes.iter().any(|x| x.same_id(&attr)) {
+        return Err(attr
+            .location
+            .error(ErrorKind::DuplicateAttribute(attr.id.clone())));
+    }
+    attributes.push(attr);
+    Ok(())
+}
+
+fn parse_command(line: &Line) -> Result<Command, Error> {
+    line.expect_header(6)?;
+    let direction = match line.field(3) {
+        "received" => "ClientToServer",
+        "generated" => "ServerToClient",
+        found => {
+            return Err(line.error(
+                3,
+                ErrorKind::Expected {
+                    expected: "'received' or 'generated'",
+                    found: found.to_string(),
+                },
+            ));
+        }
+    };
+    let mandatory = line.field(4);
+    if !matches!(mandatory, "M" | "O") {
+        return Err(line.error(4, ErrorKind::InvalidValue(mandatory.to_string())));
+    }
+    Ok(Command {
+        id: line.command_id(1)?,
+        name: line.name(2)?,
+        direction,
+        mandatory: mandatory.to_string(),
+        fields: Vec::new(),
+        location: line.location(1),
+    })
+}
+
+/// Parses a `field Name type` line, optionally followed by `optional`
+fn parse_field(line: &Line) -> Result<Field, Error> {
+    let optional = match line.fields.len() {
+        4 if line.field(3) == "optional" => true,
+        4 => {
+            return Err(line.error(
+                3,
+                ErrorKind::Expected {
+                    expected: "'optional'",
+                    found: line.field(3).to_string(),
+                },
+            ));
+        }
+        _ => {
+            line.expect_len(3)?;
+            false
+        }
+    };
+    let name = line.name(1)?;
+    let spec_type = line.field(2);
+    let (rust_type, _) = kind_to_type(&name, spec_type, &parse_quote!('a))
+        .ok_or_else(|| line.error(2, ErrorKind::UnknownType(spec_type.to_string())))?;
+    let field = Field {
+        name,
+        spec_type: spec_type.to_string(),
+        rust_type,
+        optional,
+        type_location: line.location(2),
+    };
+    // Absent fields are decoded from zeros, which only makes sense for fixed-width types
+    if field.optional && field.borrows() {
+        return Err(line.error(
+            2,
+            ErrorKind::Expected {
+                expected: "a fixed-width type for an optional field",
+                found: field.spec_type,
+            },
+        ));
+    }
+    Ok(field)
+}
+
+/// Parses a `bit 0 Name` or `bits 1..3 Name` line, both ends of the range are inclusive
+fn parse_bit_field(line: &Line, bitmap: &Bitmap) -> Result<BitField, Error> {
+    line.expect_len(3)?;
+    let position = line.field(1);
+    let range = match line.field(0) {
+        "bit" => parse_number(position).map(|x| (x, x)),
+        _ => position.split_once("..").and_then(|(low, high)| {
+            Some((parse_number(low)?, parse_number(high)?)).filter(|(low, high)| low < high)
+        }),
+    };
+    let Some((low, high)) = range.filter(|(_, high)| *high < bitmap.width as u64) else {
+        return Err(line.error(1, ErrorKind::InvalidValue(position.to_string())));
+    };
+    let (low, high) = (low as u32, high as u32);
+    if bitmap.fields.iter().any(|x| x.low <= high && low <= x.high) {
+        return Err(line.error(1, ErrorKind::OverlappingBits(position.to_string())));
+    }
+    let name = line.name(2)?;
+    if bitmap.fields.iter().any(|x| x.name == name) {
+        return Err(line.error(2, ErrorKind::DuplicateName(name)));
+    }
+    Ok(BitField { name, low, high })
+}
+
+/// Adds a command, rejecting a second definition with the same name or the same ID and direction
+fn push_command(commands: &mut Vec<Command>, command: Command) -> Result<(), Error> {
+    if commands.iter().any(|x| x.name == command.name) {
+        return Err(command
+            .location
+            .error(ErrorKind::DuplicateCommand(command.name.clone())));
+    }
+    if commands.iter().any(|x| x.same_id(&command)) {
+        return Err(command
+            .location
+            .error(ErrorKind::DuplicateCommand(command.id.clone())));
+    }
+    commands.push(command);
+    Ok(())
+}
+
+fn parse_file(path: &Path) -> Result<Definitions, Error> {
+    let file = File::open(path).map_err(|e| Location::new(path, 0, 0).error(e.into()))?;
+    let reader = BufReader::new(file);
+
+    let mut global_attributes = Vec::new();
+    let mut clusters = Vec::new();
+    let mut global_enums = Vec::new();
+    let mut extensions = Vec::new();
+
+    let mut current_cluster: Option<Cluster> = None;
+    let mut current_enum: Option<Enum> = None;
+    let mut global_bitmaps = Vec::new();
+    let mut current_bitmap: Option<Bitmap> = None;
+    let mut current_command: Option<Command> = None;
+    // The open blocks with the location of their header
+    let mut blocks: Vec<(Block, Location)> = Vec::new();
+    // The code of the enclosing `manufacturer 0x1234 { ... }` block
+    let mut manufacturer_code: Option<String> = None;
+
+    for (index, text) in reader.lines().enumerate() {
+        let text = text.map_err(|e| Location::new(path, index + 1, 0).error(e.into()))?;
+        let line = Line::new(path, index + 1, &text);
+        let Some(&(_, keyword)) = line.fields.first() else {
+            continue;
+        };
+        if keyword.starts_with('#') {
+            continue;
+        }
+
+        let block = blocks.last().map(|x| x.0);
+        match (keyword, block) {
+            ("}", _) => {
+                line.expect_len(1)?;
+                let Some((block, _)) = blocks.pop() else {
+                    return Err(line.error(0, ErrorKind::UnmatchedBrace));
+                };
+                match block {
+                    Block::Enum => {
+                        let en = current_enum.take().unwrap();
+                        match current_cluster.as_mut() {
+                            Some(cluster) => cluster.enums.push(en),
+                            None => global_enums.push(en),
+                        }
+                    }
+                    Block::Bitmap => {
+                        let bitmap = current_bitmap.take().unwrap();
+                        match current_cluster.as_mut() {
+                            Some(cluster) => cluster.bitmaps.push(bitmap),
+                            None => global_bitmaps.push(bitmap),
+                        }
+                    }
+                    Block::Client => {}
+                    Block::Command => {
+                        let command = current_command.take().unwrap();
+                        push_command(&mut current_cluster.as_mut().unwrap().commands, command)?;
+                    }
+                    Block::Extension => extensions.push(current_cluster.take().unwrap()),
+                    Block::Cluster => {
+                        let cluster = current_cluster.take().unwrap();
+                        match cluster.base {
+                            Some(_) => clusters.push(derive_cluster(&clusters, cluster)?),
+                            None => clusters.push(cluster),
+                        }
+                    }
+                    Block::Manufacturer => manufacturer_code = None,
+                }
+            }
+            (
+                "enum8" | "enum16",
+                Some(Block::Client | Block::Enum | Block::Bitmap | Block::Command),
+            ) => {
+                return Err(line.error(0, ErrorKind::UnexpectedLine(text.trim().to_string())));
+            }
+            ("enum8" | "enum16", _) => {
+                line.expect_header(3)?;
+                let width = keyword["enum".len()..].parse().unwrap();
+                current_enum = Some(Enum {
+                    repr_type: syn::parse_str(&format!("u{width}")).unwrap(),
+                    name: line.name(1)?,
+                    width,
+                    variants: Vec::new(),
+                    location: line.location(1),
+                });
+                blocks.push((Block::Enum, line.location(0)));
+            }
+            (
+                "bitmap8" | "bitmap16" | "bitmap24" | "bitmap32" | "bitmap40" | "bitmap48"
+                | "bitmap56" | "bitmap64",
+                None | Some(Block::Manufacturer | Block::Cluster | Block::Extension),
+            ) => {
+                line.expect_header(3)?;
+                current_bitmap = Some(Bitmap {
+                    name: line.name(1)?,
+                    width: keyword["bitmap".len()..].parse().unwrap(),
+                    fields: Vec::new(),
+                    location: line.location(1),
+                });
+                blocks.push((Block::Bitmap, line.location(0)));
+            }
+            ("bit" | "bits", Some(Block::Bitmap)) => {
+                let bitmap = current_bitmap.as_mut().unwrap();
+                let field = parse_bit_field(&line, bitmap)?;
+                bitmap.fields.push(field);
+            }
+            ("cluster", None | Some(Block::Manufacturer)) => {
+                let base = match line.fields.len() {
+                    6 if line.field(3) == "extends" => Some(line.field(4).to_string()),
+                    6 => {
+                        return Err(line.error(
+                            3,
+                            ErrorKind::Expected {
+                                expected: "'extends'",
+                                found: line.field(3).to_string(),
+                            },
+                        ));
+                    }
+                    _ => None,
+                };
+                line.expect_header(if base.is_some() { 6 } else { 4 })?;
+                current_cluster = Some(Cluster {
+                    name: line.name(1)?,
+                    id: line.id(2)?,
+                    base,
+                    device_types: Vec::new(),
+                    manufacturer_code: manufacturer_code.clone(),
+                    attributes: Vec::new(),
+                    enums: Vec::new(),
+                    bitmaps: Vec::new(),
+                    commands: Vec::new(),
+                    location: line.location(1),
+                });
+                blocks.push((Block::Cluster, line.location(0)));
+            }
+            ("extend", None | Some(Block::Manufacturer)) => {
+                line.expect_header(3)?;
+                current_cluster = Some(Cluster {
+                    name: line.name(1)?,
+                    id: String::new(),
+                    base: None,
+                    device_types: Vec::new(),
+                    manufacturer_code: manufacturer_code.clone(),
+                    attributes: Vec::new(),
+                    enums: Vec::new(),
+                    bitmaps: Vec::new(),
+                    commands: Vec::new(),
+                    location: line.location(1),
+                });
+                blocks.push((Block::Extension, line.location(0)));
+            }
+            ("manufacturer", None) => {
+                line.expect_header(3)?;
+                manufacturer_code = Some(line.id(1)?);
+                blocks.push((Block::Manufacturer, line.location(0)));
+            }
+            ("client", Some(Block::Cluster | Block::Extension)) => {
+                line.expect_header(2)?;
+                blocks.push((Block::Client, line.location(0)));
+            }
+            ("devices", Some(Block::Cluster))
+                if current_cluster.as_ref().unwrap().base.is_some() && line.fields.len() > 1 =>
+            {
+                let cluster = current_cluster.as_mut().unwrap();
+                for i in 1..line.fields.len() {
+                    cluster.device_types.push(line.id(i)?);
+                }
+            }
+            ("command", Some(Block::Cluster)) => {
+                current_command = Some(parse_command(&line)?);
+                blocks.push((Block::Command, line.location(0)));
+            }
+            ("field", Some(Block::Command)) => {
+                let field = parse_field(&line)?;
+                let command = current_command.as_mut().unwrap();
+                if command.fields.iter().any(|x| x.name == field.name) {
+                    return Err(line.error(1, ErrorKind::DuplicateName(field.name)));
+                }
+                if !field.optional && command.fields.iter().any(|x| x.optional) {
+                    return Err(line.error(1, ErrorKind::RequiredAfterOptional(field.name)));
+                }
+                command.fields.push(field);
+            }
+            ("attr", None | Some(Block::Cluster | Block::Extension | Block::Client)) => {
+                let side = match block {
+                    None => "Either",
+                    Some(Block::Client) => "Client",
+                    _ => "Server",
+                };
+                let attr = parse_attribute(&line, side, &manufacturer_code)?;
+                match current_cluster.as_mut() {
+                    Some(cluster) => push_attribute(&mut cluster.attributes, attr)?,
+                    None => push_attribute(&mut global_attributes, attr)?,
+                }
+            }
+            (_, Some(Block::Enum)) => {
+                line.expect_len(2)?;
+                let en = current_enum.as_mut().unwrap();
+                let value = line.field(0);
+                let Some(number) = parse_number(value) else {
+                    return Err(line.error(0, ErrorKind::InvalidNumber(value.to_string())));
+                };
+                if number > en.non_value() {
+                    return Err(line.error(
+                        0,
+                        ErrorKind::Overflow {
+                            value: value.to_string(),
+                            width: en.width,
+                        },
+                    ));
+                }
+                if en
+                    .variants
+                    .iter()
+                    .any(|x| parse_number(&x.value) == Some(number))
+                {
+                    return Err(line.error(0, ErrorKind::DuplicateEnumValue(value.to_string())));
+                }
+                let name = line.name(1)?;
+                if en.variants.iter().any(|x| x.name == name) {
+                    return Err(line.error(1, ErrorKind::DuplicateName(name)));
+                }
+                en.variants.push(EnumVariant {
+                    value: value.to_string(),
+                    name,
+                });
+            }
+            _ => {
+                return Err(line.error(0, ErrorKind::UnexpectedLine(text.trim().to_string())));
+            }
+        }
+    }
+    if let Some((_, location)) = blocks.pop() {
+        return Err(location.error(ErrorKind::UnclosedBlock));
+    }
+
+    Ok(Definitions {
+        attributes: global_attributes,
+        clusters,
+        enums: global_enums,
+        bitmaps: global_bitmaps,
+        extensions,
+    })
+}
+
+/// Adds the attributes, enums and bitmaps of extensions to the clusters they extend, including
+/// the variants of those clusters.
+fn apply_extensions(files: &mut [(String, Definitions)]) -> Result<(), Error> {
+    let extensions = files
+        .iter_mut()
+        .flat_map(|(_, defs)| defs.extensions.drain(..))
+        .collect::<Vec<_>>();
+    for extension in extensions {
+        let mut found = false;
+        for cluster in files.iter_mut().flat_map(|(_, defs)| &mut defs.clusters) {
+            if cluster.name == extension.name || cluster.base.as_ref() == Some(&extension.name) {
+                found = true;
+                for attr in &extension.attributes {
+                    push_attribute(&mut cluster.attributes, attr.clone())?;
+                }
+                cluster.enums.extend(extension.enums.iter().cloned());
+                cluster.bitmaps.extend(extension.bitmaps.iter().cloned());
+            }
+        }
+        if !found {
+            return Err(extension
+                .location
+                .error(ErrorKind::UnknownCluster(extension.name)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the ranges only refer to attributes which exist
+fn check_references(attributes: &[Attribute], cluster: Option<&Cluster>) -> Result<(), Error> {
+    for attr in attributes {
+        let Some((min, max)) = bounds(attr) else {
+            continue;
+        };
+        for bound in [min, max] {
+            let defined = syn::parse_str::<LitInt>(bound).is_ok()
+                || cluster.is_some_and(|c| c.attributes.iter().any(|x| x.name == bound));
+            if !defined {
+                return Err(attr
+                    .range_location
+                    .error(ErrorKind::UndefinedReference(bound.to_string())));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The enumeration or bitmap a type refers to by name: whether it is an enumeration, its
+/// width and its name. Plain `enum8` types name the enumeration after the attribute or field.
+fn referenced_type<'a>(spec_type: &'a str, name: &'a str) -> Option<(bool, u32, &'a str)> {
+    let spec_type = ["array:", "set:", "bag:"]
+        .iter()
+        .find_map(|x| spec_type.strip_prefix(x))
+        .unwrap_or(spec_type);
+    let (kind, type_name) = match spec_type.split_once(':') {
+        Some((kind, type_name)) => (kind, type_name),
+        None if spec_type.starts_with("enum") => (spec_type, name),
+        None => return None,
+    };
+    match (kind.strip_prefix("enum"), kind.strip_prefix("map")) {
+        (Some(width), _) => Some((true, width.parse().ok()?, type_name)),
+        (_, Some(width)) => Some((false, width.parse().ok()?, type_name)),
+        _ => None,
+    }
+}
+
+/// Checks that the enumerations and bitmaps of a scope have distinct names and that
+/// the types of its attributes and command fields refer to those in scope, which are
+/// `enums` and `bitmaps` together with those of the enclosing file in `outer`.
+fn check_types(
+    enums: &[Enum],
+    bitmaps: &[Bitmap],
+    outer: Option<&Definitions>,
+    types: impl IntoIterator<Item = (String, String, Location)>,
+) -> Result<(), Error> {
+    let names = enums
+        .iter()
+        .map(|x| (&x.name, &x.location))
+        .chain(bitmaps.iter().map(|x| (&x.name, &x.location)));
+    for (i, (name, location)) in names.clone().enumerate() {
+        if names.clone().take(i).any(|(x, _)| x == name) {
+            return Err(location.error(ErrorKind::DuplicateName(name.clone())));
+        }
+    }
+    let outer_enums = outer.map_or(&[][..], |x| &x.enums);
+    let outer_bitmaps = outer.map_or(&[][..], |x| &x.bitmaps);
+    for (spec_type, name, location) in types {
+        let Some((is_enum, width, type_name)) = referenced_type(&spec_type, &name) else {
+            continue;
+        };
+        let defined = if is_enum {
+            enums
+                .iter()
+                .chain(outer_enums)
+                .find(|x| x.name == type_name)
+                .is_some_and(|x| x.width == width)
+        } else {
+            bitmaps
+                .iter()
+                .chain(outer_bitmaps)
+                .find(|x| x.name == type_name)
+                .is_some_and(|x| x.width == width)
+        };
+        if !defined {
+            return Err(location.error(ErrorKind::UnknownType(spec_type)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the names and types of all definitions, after extensions have been applied
+fn check_names(files: &[(String, Definitions)]) -> Result<(), Error> {
+    let clusters = files.iter().flat_map(|(_, defs)| &defs.clusters);
+    for (i, cluster) in clusters.clone().enumerate() {
+        if clusters.clone().take(i).any(|x| x.name == cluster.name) {
+            return Err(cluster
+                .location
+                .error(ErrorKind::DuplicateName(cluster.name.clone())));
+        }
+    }
+    let attribute_types = |attributes: &[Attribute]| {
+        attributes
+            .iter()
+            .map(|x| (x.spec_type.clone(), x.name.clone(), x.type_location.clone()))
+            .collect::<Vec<_>>()
+    };
+    for (_, defs) in files {
+        check_types(
+            &defs.enums,
+            &defs.bitmaps,
+            None,
+            attribute_types(&defs.attributes),
+        )?;
+        for cluster in &defs.clusters {
+            let fields = cluster
+                .commands
+                .iter()
+                .flat_map(|x| &x.fields)
+                .map(|x| (x.spec_type.clone(), x.name.clone(), x.type_location.clone()));
+            check_types(
+                &cluster.enums,
+                &cluster.bitmaps,
+                Some(defs),
+                attribute_types(&cluster.attributes)
+                    .into_iter()
+                    .chain(fields),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Merges a variant with its base cluster. Attributes of the variant replace the base
+/// attributes with the same ID, side and manufacturer code, all other attributes are inherited.
+/// Commands are replaced by those with the same ID and direction.
+fn derive_cluster(clusters: &[Cluster], variant: Cluster) -> Result<Cluster, Error> {
+    let base = variant.base.as_deref().unwrap();
+    let Some(base) = clusters.iter().find(|x| x.name == base) else {
+        return Err(variant
+            .location
+            .error(ErrorKind::UnknownCluster(base.to_string())));
+    };
+    if parse_id(&base.id) != parse_id(&variant.id) {
+        return Err(variant
+            .location
+            .error(ErrorKind::VariantId(base.id.clone())));
+    }
+
+    let mut attributes = base.attributes.clone();
+    for attr in variant.attributes {
+        match attributes.iter_mut().find(|x| x.same_id(&attr)) {
+            Some(x) => *x = attr,
+            None => attributes.push(attr),
+        }
+    }
+    let mut enums = base.enums.clone();
+    enums.extend(variant.enums);
+    let mut bitmaps = base.bitmaps.clone();
+    bitmaps.extend(variant.bitmaps);
+    let mut commands = base.commands.clone();
+    for command in variant.commands {
+        match commands.iter_mut().find(|x| x.same_id(&command)) {
+            Some(x) => *x = command,
+            None => commands.push(command),
+        }
+    }
+
+    Ok(Cluster {
+        attributes,
+        enums,
+        bitmaps,
+        commands,
+        ..variant
+    })
+}
+
+fn parse_range(
+    attr: &Attribute,
+    cluster: Option<&Cluster>,
+    value: impl Fn(TokenStream) -> TokenStream,
+) -> TokenStream {
+    match (attr.spec_type.as_str(), attr.range.as_str()) {
+        // (_, "-") => quote! { AttributeRange::Ignore },
+        (_, "value") => quote! { AttributeRange::Value },
+        (_, "full-non" | "-") => quote! { AttributeRange::FullWithNone },
+        (_, "full") => quote! { AttributeRange::Full },
+        (x, _) if is_sized(x) => {
+            let s: Expr = syn::parse_str(&attr.range).unwrap();
+            quote! { AttributeRange::Size(#s) }
+        }
+        _ => {
+            let (min, max) = bounds(attr).expect("ranges are validated by the parser");
+            let parse_bound = |bound: &str| -> TokenStream {
+                if let Ok(lit) = syn::parse_str::<LitInt>(bound) {
+                    let literal = kind_to_literal(&lit, &attr.spec_type);
+                    let value = value(match bitmap_name(&attr.spec_type) {
+                        Some(_) => bitmap_payload(&attr.spec_type, literal),
+                        None => literal,
+                    });
+                    quote! {
+                        ValueOrAttributeReference::Value(#value)
+                    }
+                } else {
+                    let attr_ref = cluster
+                        .and_then(|c| c.attributes.iter().find(|a| a.name == bound))
+                        .expect("references are validated by the parser");
+                    let attr_lit: Lit = syn::parse_str(&attr_ref.id).unwrap();
+                    quote! {
+                        ValueOrAttributeReference::AttributeReference(#attr_lit)
+                    }
+                }
+            };
+
+            let min_tokens = parse_bound(min);
+            let max_tokens = parse_bound(max);
+
+            quote! {
+                AttributeRange::InclusiveRange(
+                    #min_tokens,
+                    #max_tokens
+                )
+            }
+        }
+    }
+}
+
+/// The name of the type wrapper, which is also the name of the matching `ZclValue` variant
+fn type_name(attr: &Attribute) -> TokenStream {
+    attr.rust_type.clone().into_iter().take(1).collect()
+}
+
+/// The payload of the type wrapper for the attribute's default value
+fn default_payload(attr: &Attribute) -> Option<TokenStream> {
+    let rust_type = &attr.rust_type;
+    match attr.default.as_str() {
+        "-" => None,
+        "non"
+            if ["array:", "set:", "bag:"]
+                .iter()
+                .any(|x| attr.spec_type.starts_with(x)) =>
+        {
+            Some(quote! { None })
+        }
+        "non" => Some(quote! { <#rust_type>::NON_VALUE.unwrap() }),
+        def => {
+            let default: Expr = syn::parse_str(def).unwrap();
+            Some(match attr.spec_type.as_str() {
+                x if x.starts_with("enum8:") => {
+                    let chosen_enum = format_ident!("{}", x.strip_prefix("enum8:").unwrap());
+                    quote! { #chosen_enum::from_value(#default) }
+                }
+                x if x.starts_with("enum16:") => {
+                    let chosen_enum = format_ident!("{}", x.strip_prefix("enum16:").unwrap());
+                    quote! { #chosen_enum::from_value(#default) }
+                }
+                "enum8" | "enum16" => {
+                    let ident = format_ident!("{}", attr.name);
+                    quote! { #ident::from_value(#default) }
+                }
+                "octstr" | "string" | "octstr16" | "string16" | "bool" => {
+                    quote! { Some(#default) }
+                }
+                "lossystring" | "lossystring16" => quote! { Some(#default.as_bytes()) },
+                x if bitmap_name(x).is_some() => bitmap_payload(x, quote! { #default }),
+                _ => quote! { #default },
+            })
+        }
+    }
+}
+
+/// The flags of a `map8:Name` bitmap with the given raw bits
+fn bitmap_payload(spec_type: &str, bits: TokenStream) -> TokenStream {
+    let name = format_ident!("{}", bitmap_name(spec_type).unwrap());
+    quote! { #name::from_bits(#bits) }
+}
+
+/// Wraps a payload of the attribute's type into a `ZclValue`. Enumerations and bitmaps
+/// are stored by their raw value.
+fn erased_value(attr: &Attribute, payload: TokenStream) -> TokenStream {
+    let name = type_name(attr);
+    let payload = match attr.spec_type.split(':').next() {
+        Some("enum8") => quote! { (#payload) as u8 },
+        Some("enum16") => quote! { (#payload) as u16 },
+        _ if bitmap_name(&attr.spec_type).is_some() => quote! { (#payload).bits() },
+        _ => payload,
+    };
+    quote! { ZclValue::#name(#name(#payload)) }
+}
+
+fn generate_descriptor(attr: &Attribute, cluster: Option<&Cluster>) -> TokenStream {
+    let id: Lit = syn::parse_str(&attr.id).unwrap();
+    let name = &attr.name;
+    let rust_type = &attr.rust_type;
+    let default = match default_payload(attr) {
+        Some(payload) => {
+            let value = erased_value(attr, payload);
+            quote! { Some(#value) }
+        }
+        None => quote! { None },
+    };
+    let range = parse_range(attr, cluster, |x| erased_value(attr, x));
+    let mandatory = attr.mandatory == "M";
+    let readable = attr.access.contains('R');
+    let writable = write_access(attr);
+    let reportable = attr.access.contains('P');
+    let scene = attr.access.contains('S');
+    let side = format_ident!("{}", attr.side);
+    let manufacturer_code = manufacturer_code(&attr.manufacturer_code);
+
+    quote! {
+        AttributeDescriptor {
+            code: #id,
+            name: #name,
+            type_id: <#rust_type>::ID,
+            side: AttributeSide::#side,
+            readable: #readable,
+            writable: #writable,
+            reportable: #reportable,
+            scene: #scene,
+            mandatory: #mandatory,
+            manufacturer_code: #manufacturer_code,
+            default: #default,
+            range: #range,
+        },
+    }
+}
+
+/// The `WriteAccess` variant of the attribute, `R*W` marks attributes whose write support
+/// is optional
+fn write_access_name(attr: &Attribute) -> &'static str {
+    if attr.access.contains("*W") {
+        "Optional"
+    } else if attr.access.contains('W') {
+        "Mandatory"
+    } else {
+        "No"
+    }
+}
+
+fn write_access(attr: &Attribute) -> TokenStream {
+    let variant = format_ident!("{}", write_access_name(attr));
+    quote! { WriteAccess::#variant }
+}
+
+fn manufacturer_code(code: &Option<String>) -> TokenStream {
+    match code {
+        Some(code) => {
+            let code: Lit = syn::parse_str(code).unwrap();
+            quote! { Some(#code) }
+        }
+        None => quote! { None },
+    }
+}
+
+fn generate_descriptors(attributes: &[Attribute], cluster: Option<&Cluster>) -> TokenStream {
+    let mut attributes = attributes.iter().collect::<Vec<_>>();
+    attributes.sort_by_key(|attr| {
+        (
+            parse_id(&attr.id),
+            attr.manufacturer_code.as_deref().map(parse_id),
+        )
+    });
+    let descriptors = attributes
+        .iter()
+        .map(|attr| generate_descriptor(attr, cluster));
+    quote! {
+        /// Type-erased descriptions of all attributes sorted by ID and manufacturer code,
+        /// e.g. for attribute discovery
+        pub const ATTRIBUTES: &[AttributeDescriptor<'static>] = &[
+            #(#descriptors)*
+        ];
+    }
+}
+
+fn generate_attribute_code(attr: &Attribute, cluster: Option<&Cluster>) -> TokenStream {
+    let id: Lit = syn::parse_str(&attr.id).unwrap();
+    let name = &attr.name;
+    let rust_type = &attr.rust_type;
+    let default = match default_payload(attr) {
+        Some(payload) => quote! { Some(#rust_type(#payload)) },
+        None => quote! { None },
+    };
+    let range = parse_range(attr, cluster, |x| quote! { #rust_type(#x) });
+    let mandatory = attr.mandatory == "M";
+
+    // Parse access flags
+    let readable = attr.access.contains('R');
+    let writable = write_access(attr);
+    let reportable = attr.access.contains('P');
+    let scene = attr.access.contains('S');
+    let side = format_ident!("{}", attr.side);
+    let manufacturer_code = manufacturer_code(&attr.manufacturer_code);
+
+    let name_ident = format_ident!("{}", name.to_case(Case::UpperSnake));
+
+    let attr_def = quote! {
+        pub const #name_ident: Attribute<'static, #rust_type> = Attribute {
+            code: #id,
+            name: #name,
+            side: AttributeSide::#side,
+            readable: #readable,
+            writable: #writable,
+            reportable: #reportable,
+            scene: #scene,
+            mandatory: #mandatory,
+            manufacturer_code: #manufacturer_code,
+            default: #default,
+            range: #range,
+        };
+    };
+
+    let attr_def_str =
+        prettyplease::unparse(&syn::parse_file(attr_def.to_string().as_str()).unwrap());
+    // The definition is shown for reference only: it uses names which are not in scope
+    // of a doctest, so compiling it would fail.
+    quote! {
+        #[doc = "```rust,ignore"]
+        #[doc = #attr_def_str]
+        #[doc = "```"]
+        #attr_def
+    }
+}
+
+fn generate_cluster(cluster: &Cluster) -> TokenStream {
+    let name = &cluster.name;
+    let cluster_name = format_ident!("{}_CLUSTER", cluster.name.to_case(Case::UpperSnake));
+    let id: Lit = syn::parse_str(&cluster.id).unwrap();
+    let mod_name = format_ident!("{}", cluster.name.to_case(Case::Snake));
+
+    let fields = cluster.attributes.iter().map(|attr| {
+        let field_name = format_ident!("{}", attr.name.to_case(Case::Snake));
+        let val_name = format_ident!("{}", attr.name.to_case(Case::UpperSnake));
+        quote! {
+            #field_name: self::#mod_name::#val_name,
+        }
+    });
+    let struct_name = format_ident!("{}Attrs", cluster.name.to_case(Case::UpperCamel));
+    let cluster_def = quote! {
+        pub const #cluster_name: ::zcl::Cluster<'static,self::#mod_name::#struct_name> = ::zcl::Cluster {
+            code: #id,
+            name: #name,
+            attributes: self::#mod_name::ATTRIBUTES,
+            meta: self::#mod_name::#struct_name {
+                #(#fields)*
+            },
+        };
+    };
+    let cluster_def_str =
+        prettyplease::unparse(&syn::parse_file(cluster_def.to_string().as_str()).unwrap());
+    // Shown for reference only, see generate_attribute_code
+    quote! {
+        #[doc = "```rust,ignore"]
+        #[doc = #cluster_def_str]
+        #[doc = "```"]
+        #cluster_def
+    }
+}
+
+fn generate_cluster_struct(cluster: &Cluster) -> TokenStream {
+    let struct_name = format_ident!("{}Attrs", cluster.name.to_case(Case::UpperCamel));
+    let fields = cluster.attributes.iter().map(|attr| {
+        let field_name = format_ident!("{}", attr.name.to_case(Case::Snake));
+        let ty = &attr.rust_type;
+        quote! {
+            pub #field_name: Attribute<'static, #ty>,
+        }
+    });
+
+    let attrs_array = cluster.attributes.iter().map(|attr| {
+        let name = &attr.name;
+        let ty_str = attr.rust_type.to_string();
+        quote! {
+            (#name, #ty_str)
+        }
+    });
+
+    let n_attrs = cluster.attributes.len();
+    quote! {
+        pub struct #struct_name {
+            #(#fields)*
+        }
+
+        impl #struct_name {
+            pub fn attrs(&self) -> [(&'static str, &'static str); #n_attrs] {
+                [
+                    #(#attrs_array),*
+                ]
+            }
+        }
+    }
+}
+
+fn generate_enum(en: &Enum) -> TokenStream {
+    let ident = format_ident!("{}", en.name.to_case(Case::UpperCamel));
+    let repr_type = &en.repr_type;
+
+    // The non-value gets a `None` variant unless the definition names it
+    let is_non_value = |x: &EnumVariant| parse_number(&x.value) == Some(en.non_value());
+    let mut variants = en.variants.clone();
+    if !variants.iter().any(is_non_value) {
+        variants.push(EnumVariant {
+            value: format!("{:#x}", en.non_value()),
+            name: "None".to_string(),
+        })
+    }
+
+    let enum_variants = variants
+        .iter()
+        .map(|x| {
+            let name = format_ident!("{}", x.name.to_case(Case::UpperCamel));
+            let value: Expr = syn::parse_str(&x.value).unwrap();
+            quote! {
+                #name = #value,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let from_value_arms = variants
+        .iter()
+        .map(|x| {
+            let name = format_ident!("{}", x.name.to_case(Case::UpperCamel));
+            let value: Expr = syn::parse_str(&x.value).unwrap();
+            quote! {
+                #value => Ok(Self::#name),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let non_value = variants
+        .iter()
+        .find(|x| is_non_value(x))
+        .map(|x| {
+            let name = format_ident!("{}", x.name.to_case(Case::UpperCamel));
+            quote! { Self::#name }
+        })
+        .unwrap();
+
+    quote! {
+        #[repr(#repr_type)]
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub enum #ident {
+            #(#enum_variants)*
+        }
+
+        impl ::zcl::types::ZclEnum for #ident {
+            type Repr = #repr_type;
+            const NON_VALUE: Self = #non_value;
+
+            fn into_raw(self) -> #repr_type {
+                self as #repr_type
+            }
+
+            fn try_from_raw(value: #repr_type) -> Option<Self> {
+                Self::try_from_value(value).ok()
+            }
+        }
+
+        impl #ident {
+            /// Converts from the raw value, failing with [`ZclError::ValueOutOfRange`](::zcl::ZclError::ValueOutOfRange)
+            /// for values without a variant.
+            pub const fn try_from_value(value: #repr_type) -> Result<Self, ::zcl::ZclError> {
+                match value {
+                    #(#from_value_arms)*
+                    _ => Err(::zcl::ZclError::ValueOutOfRange)
+                }
+            }
+
+            /// Converts from the raw value in constants, where an unknown value fails
+            /// the build.
+            ///
+            /// # Panics
+            ///
+            /// If there is no variant for the value, see [`Self::try_from_value`] otherwise.
+            pub const fn from_value(value: #repr_type) -> Self {
+                match Self::try_from_value(value) {
+                    Ok(x) => x,
+                    Err(_) => panic!("Failed to convert value to enum"),
+                }
+            }
+        }
+
+        impl TryFrom<#repr_type> for #ident {
+            type Error = ::zcl::ZclError;
+
+            fn try_from(value: #repr_type) -> Result<Self, ::zcl::ZclError> {
+                Self::try_from_value(value)
+            }
+        }
+
+        impl From<#ident> for #repr_type {
+            fn from(value: #ident) -> Self {
+                value as #repr_type
+            }
+        }
+    }
+}
+
+fn generate_bitmap(bitmap: &Bitmap) -> TokenStream {
+    let ident = format_ident!("{}", bitmap.name.to_case(Case::UpperCamel));
+    let repr_type = bitmap.repr_type();
+    let hex = |x: u64| LitInt::new(&format!("{x:#x}"), proc_macro2::Span::call_site());
+    let field_mask = |x: &BitField| (u64::MAX >> (63 - (x.high - x.low))) << x.low;
+    let all = hex(bitmap
+        .fields
+        .iter()
+        .map(field_mask)
+        .fold(0, |acc, x| acc | x));
+
+    let fields = bitmap.fields.iter().map(|x| {
+        let low = proc_macro2::Literal::u32_unsuffixed(x.low);
+        if x.low == x.high {
+            let name = format_ident!("{}", x.name.to_case(Case::UpperSnake));
+            let doc = format!("Bit {low}");
+            return quote! {
+                #[doc = #doc]
+                pub const #name: Self = Self(1 << #low);
+            };
+        }
+        let getter = format_ident!("{}", x.name.to_case(Case::Snake));
+        let setter = format_ident!("set_{}", x.name.to_case(Case::Snake));
+        let mask = hex(field_mask(x) >> x.low);
+        let doc = format!("Bits {} to {}", x.low, x.high);
+        quote! {
+            #[doc = #doc]
+            pub const fn #getter(self) -> #repr_type {
+                (self.0 >> #low) & #mask
+            }
+
+            /// Sets the bits to the low bits of `value`
+            pub fn #setter(&mut self, value: #repr_type) {
+                self.0 = (self.0 & !(#mask << #low)) | ((value & #mask) << #low);
+            }
+        }
+    });
+
+    quote! {
+        #[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+        pub struct #ident(#repr_type);
+
+        impl #ident {
+            #(#fields)*
+
+            pub const EMPTY: Self = Self(0);
+            /// All bits which are not reserved
+            pub const ALL: Self = Self(#all);
+
+            /// Converts from the raw bits, clearing the reserved bits.
+            pub const fn from_bits(bits: #repr_type) -> Self {
+                Self(bits & Self::ALL.0)
+            }
+
+            pub const fn bits(self) -> #repr_type {
+                self.0
+            }
+
+            /// Whether all bits set in `other` are set in `self`
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub fn insert(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+
+            pub fn remove(&mut self, other: Self) {
+                self.0 &= !other.0;
+            }
+        }
+
+        impl ::core::ops::BitOr for #ident {
+            type Output = Self;
+
+            fn bitor(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+        }
+
+        impl ::zcl::types::ZclBitmap for #ident {
+            type Repr = #repr_type;
+
+            fn into_raw(self) -> #repr_type {
+                self.0
+            }
+
+            fn from_raw(value: #repr_type) -> Self {
+                Self::from_bits(value)
+            }
+        }
+    }
+}
+
+fn generate_command(command: &Command) -> TokenStream {
+    let ident = format_ident!("{}", command.name.to_case(Case::UpperCamel));
+    let id: Lit = syn::parse_str(&command.id).unwrap();
+    let direction = format_ident!("{}", command.direction);
+    let doc = format!(
+        "{} ({}), {} by the server",
+        command.name.to_case(Case::Title),
+        command.id,
+        command.direction_name(),
+    );
+    let mandatory = command.mandatory == "M";
+    let (generics, data) = match command.borrows() {
+        true => (quote! { <'a> }, quote! { &'a [u8] }),
+        false => (quote! {}, quote! { &[u8] }),
+    };
+    let decode_impl = match command.borrows() {
+        true => quote! { impl<'a> ZclDecode<'a> for #ident<'a> },
+        false => quote! { impl ZclDecode<'_> for #ident },
+    };
+
+    let names = command
+        .fields
+        .iter()
+        .map(|x| format_ident!("{}", x.name.to_case(Case::Snake)))
+        .collect::<Vec<_>>();
+    let types = command.fields.iter().map(|x| &x.rust_type);
+    if command.fields.is_empty() {
+        return quote! {
+            #[doc = #doc]
+            #[derive(PartialEq, Debug, Copy, Clone)]
+            pub struct #ident;
+
+            impl #ident {
+                pub const ID: u8 = #id;
+                pub const DIRECTION: ::zcl::frame::Direction = ::zcl::frame::Direction::#direction;
+                pub const MANDATORY: bool = #mandatory;
+            }
+
+            impl ZclCompatibleType for #ident {
+                fn len(&self) -> usize {
+                    0
+                }
+            }
+
+            impl ZclEncode for #ident {
+                fn to_bytes(&self, _data: &mut [u8]) -> Result<usize, ::zcl::ZclError> {
+                    Ok(0)
+                }
+            }
+
+            impl ZclDecode<'_> for #ident {
+                fn from_bytes(_data: &[u8]) -> Result<Self, ::zcl::ZclError> {
+                    Ok(Self)
+                }
+            }
+        };
+    }
+
+    // Every field but the last advances the payload for the next one. Optional fields
+    // missing at the end of the payload are decoded from zeros.
+    let decode_fields = command
+        .fields
+        .iter()
+        .zip(&names)
+        .enumerate()
+        .map(|(i, (field, name))| {
+            let rust_type = &field.rust_type;
+            let advance = (i + 1 < command.fields.len()).then(|| {
+                quote! { let data = data.get(#name.len()..).unwrap_or_default(); }
+            });
+            let decode = match field.optional {
+                true => quote! {
+                    let #name = match data.is_empty() {
+                        true => <#rust_type>::from_bytes(&[0; 16])?,
+                        false => <#rust_type>::from_bytes(data)?,
+                    };
+                },
+                false => quote! { let #name = <#rust_type>::from_bytes(data)?; },
+            };
+            quote! {
+                #decode
+                #advance
+            }
+        });
+
+    quote! {
+        #[doc = #doc]
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub struct #ident #generics {
+            #(pub #names: #types,)*
+        }
+
+        impl #generics #ident #generics {
+            pub const ID: u8 = #id;
+            pub const DIRECTION: ::zcl::frame::Direction = ::zcl::frame::Direction::#direction;
+            pub const MANDATORY: bool = #mandatory;
+        }
+
+        impl #generics ZclCompatibleType for #ident #generics {
+            fn len(&self) -> usize {
+                #(self.#names.len())+*
+            }
+        }
+
+        impl #generics ZclEncode for #ident #generics {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ::zcl::ZclError> {
+                let mut offset = 0;
+                #(offset += self.#names.to_bytes(&mut data[offset..])?;)*
+                Ok(offset)
+            }
+        }
+
+        #decode_impl {
+            fn from_bytes(data: #data) -> Result<Self, ::zcl::ZclError> {
+                #(#decode_fields)*
+                Ok(Self { #(#names),* })
+            }
+        }
+    }
+}
+
+/// The sorted command ID lists of the cluster and, if it has commands, a `Command` enum
+/// decoding any of them
+fn generate_commands(cluster: &Cluster) -> TokenStream {
+    let ids = |direction: &str| {
+        let mut ids = cluster
+            .commands
+            .iter()
+            .filter(|x| x.direction == direction)
+            .map(|x| parse_id(&x.id) as u8)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    let received = ids("ClientToServer");
+    let generated = ids("ServerToClient");
+    let mut tokens = quote! {
+        /// The IDs of the commands received by the server, e.g. for command discovery
+        pub const RECEIVED_COMMANDS: &[u8] = &[#(#received),*];
+        /// The IDs of the commands generated by the server, e.g. for command discovery
+        pub const GENERATED_COMMANDS: &[u8] = &[#(#generated),*];
+    };
+    if cluster.commands.is_empty() {
+        return tokens;
+    }
+    for command in &cluster.commands {
+        tokens.extend(generate_command(command));
+    }
+
+    let borrows = cluster.commands.iter().any(Command::borrows);
+    let (generics, data) = match borrows {
+        true => (quote! { <'a> }, quote! { &'a [u8] }),
+        false => (quote! {}, quote! { &[u8] }),
+    };
+    let variants = cluster.commands.iter().map(|x| {
+        let ident = format_ident!("{}", x.name.to_case(Case::UpperCamel));
+        match x.borrows() {
+            true => quote! { #ident(#ident<'a>) },
+            false => quote! { #ident(#ident) },
+        }
+    });
+    let idents = cluster
+        .commands
+        .iter()
+        .map(|x| format_ident!("{}", x.name.to_case(Case::UpperCamel)))
+        .collect::<Vec<_>>();
+    let decode_arms = cluster.commands.iter().zip(&idents).map(|(x, ident)| {
+        let id: Lit = syn::parse_str(&x.id).unwrap();
+        let direction = format_ident!("{}", x.direction);
+        quote! {
+            (::zcl::frame::Direction::#direction, #id) => Self::#ident(#ident::from_bytes(data)?),
+        }
+    });
+    let from_impls = cluster.commands.iter().zip(&idents).map(|(x, ident)| {
+        let (command_generics, command_type) = match x.borrows() {
+            true => (quote! { <'a> }, quote! { #ident<'a> }),
+            false => (generics.clone(), quote! { #ident }),
+        };
+        quote! {
+            impl #command_generics From<#command_type> for Command #generics {
+                fn from(value: #command_type) -> Self {
+                    Self::#ident(value)
+                }
+            }
+        }
+    });
+
+    tokens.extend(quote! {
+        /// The cluster specific commands in both directions
+        #[derive(PartialEq, Debug, Copy, Clone)]
+        pub enum Command #generics {
+            #(#variants,)*
+        }
+
+        impl #generics Command #generics {
+            /// Decodes the payload of the command with the ID and direction given by the
+            /// [`FrameHeader`](::zcl::frame::FrameHeader).
+            pub fn decode(
+                direction: ::zcl::frame::Direction,
+                command_id: u8,
+                data: #data,
+            ) -> Result<Self, ::zcl::ZclError> {
+                Ok(match (direction, command_id) {
+                    #(#decode_arms)*
+                    _ => return Err(::zcl::ZclError::UnknownCommand(command_id)),
+                })
+            }
+
+            pub fn id(&self) -> u8 {
+                match self {
+                    #(Self::#idents(_) => #idents::ID,)*
+                }
+            }
+
+            pub fn direction(&self) -> ::zcl::frame::Direction {
+                match self {
+                    #(Self::#idents(_) => #idents::DIRECTION,)*
+                }
+            }
+        }
+
+        impl #generics ZclCompatibleType for Command #generics {
+            fn len(&self) -> usize {
+                match self {
+                    #(Self::#idents(x) => x.len(),)*
+                }
+            }
+        }
+
+        impl #generics ZclEncode for Command #generics {
+            fn to_bytes(&self, data: &mut [u8]) -> Result<usize, ::zcl::ZclError> {
+                match self {
+                    #(Self::#idents(x) => x.to_bytes(data),)*
+                }
+            }
+        }
+
+        #(#from_impls)*
+    });
+    tokens
+}
+
+fn generate_cluster_descriptor(cluster: &Cluster, clusters: &[Cluster]) -> TokenStream {
+    let id: Lit = syn::parse_str(&cluster.id).unwrap();
+    let name = &cluster.name;
+    let manufacturer_code = manufacturer_code(&cluster.manufacturer_code);
+    let variants = clusters
+        .iter()
+        .filter(|x| x.base.as_ref() == Some(&cluster.name))
+        .map(|x| {
+            let mod_name = format_ident!("{}", x.name.to_case(Case::Snake));
+            quote! { super::#mod_name::DESCRIPTOR }
+        });
+    let device_types = cluster
+        .device_types
+        .iter()
+        .map(|x| syn::parse_str::<Lit>(x).unwrap());
+    quote! {
+        /// Type-erased description of the cluster
+        pub const DESCRIPTOR: ::zcl::clusters::ClusterDescriptor<'static> =
+            ::zcl::clusters::ClusterDescriptor {
+                code: #id,
+                name: #name,
+                manufacturer_code: #manufacturer_code,
+                attributes: ATTRIBUTES,
+                variants: &[#(#variants),*],
+                device_types: &[#(#device_types),*],
+            };
+    }
+}
+
+/// The table of all clusters sorted by ID and manufacturer code, backing the lookups in `zcl::clusters`
+fn generate_cluster_table(clusters: &mut [((u16, Option<u16>), TokenStream)]) -> TokenStream {
+    clusters.sort_by_key(|(key, _)| *key);
+    let descriptors = clusters.iter().map(|(_, descriptor)| descriptor);
+    quote! {
+        #[allow(dead_code)]
+        const CLUSTER_TABLE: &[::zcl::clusters::ClusterDescriptor<'static>] = &[
+            #(#descriptors),*
+        ];
+    }
+}
+
+/// Generates the definitions of the given cluster definition files. Each file becomes
+/// a module named after the file stem, holding its enums, global attributes and one
+/// submodule and `XXX_CLUSTER` constant per cluster. A private `CLUSTER_TABLE` lists
+/// all clusters sorted by ID.
+///
+/// The generated code refers to the runtime types as `::zcl::types`. Files may extend
+/// the clusters of other files passed in the same call.
+pub fn generate<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<TokenStream, Error> {
+    let mut generated = TokenStream::new();
+    let mut cluster_table = Vec::new();
+
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let filename_stem = path.file_stem().unwrap().to_string_lossy().to_string();
+        if syn::parse_str::<Ident>(&filename_stem).is_err() {
+            return Err(Location::new(path, 0, 0).error(ErrorKind::InvalidName(filename_stem)));
+        }
+        files.push((filename_stem, parse_file(path)?));
+    }
+    // Vendor files may extend the clusters of any other file
+    apply_extensions(&mut files)?;
+    check_names(&files)?;
+    for (_, defs) in &files {
+        check_references(&defs.attributes, None)?;
+        for cluster in &defs.clusters {
+            check_references(&cluster.attributes, Some(cluster))?;
+        }
+    }
+
+    for (filename_stem, defs) in &files {
+        let mod_name = format_ident!("{}", filename_stem);
+        let file_mod = mod_name.clone();
+        let Definitions {
+            attributes: global_attributes,
+            clusters,
+            enums,
+            bitmaps,
+            ..
+        } = defs;
+
+        let mut mod_content = TokenStream::new();
+        for en in enums {
+            mod_content.extend(generate_enum(en));
+        }
+        for bitmap in bitmaps {
+            mod_content.extend(generate_bitmap(bitmap));
+        }
+
+        for attr in global_attributes {
+            mod_content.extend(generate_attribute_code(attr, None));
+        }
+        if !global_attributes.is_empty() {
+            mod_content.extend(generate_descriptors(global_attributes, None));
+        }
+
+        for cluster in clusters {
+            let mut inner_mod_content = TokenStream::new();
+            let mod_name = format_ident!("{}", cluster.name.to_case(Case::Snake));
+            let mut attr_table = "".to_string();
+            attr_table +=
+                "| id | name | type | range | access | writable | default | mandatory | side |\n";
+            attr_table +=
+                "|----|------|------|-------|--------|----------|---------|-----------|------|\n";
+
+            for enu in &cluster.enums {
+                inner_mod_content.extend(generate_enum(enu));
+            }
+            for bitmap in &cluster.bitmaps {
+                inner_mod_content.extend(generate_bitmap(bitmap));
+            }
+            for attr in &cluster.attributes {
+                inner_mod_content.extend(generate_attribute_code(attr, Some(cluster)));
+                attr_table += &format!(
+                    "| {} | [{}]({mod_name}::{}) | {} | {} | {} | {} | {} | {} | {} |\n",
+                    match &attr.manufacturer_code {
+                        Some(code) => format!("{} (manufacturer {code})", attr.id),
+                        None => attr.id.clone(),
+                    },
+                    attr.name,
+                    attr.name.to_case(Case::UpperSnake),
+                    attr.rust_type_doc,
+                    attr.range,
+                    attr.access.replace('*', "\\*"),
+                    match write_access_name(attr) {
+                        "Optional" => "optional",
+                        "Mandatory" => "✅",
+                        _ => "❌",
+                    },
+                    attr.default,
+                    if attr.mandatory == "M" { "✅" } else { "❌" },
+                    attr.side,
+                );
+            }
+            inner_mod_content.extend(generate_descriptors(&cluster.attributes, Some(cluster)));
+            inner_mod_content.extend(generate_cluster_descriptor(cluster, clusters));
+            inner_mod_content.extend(generate_cluster_struct(cluster));
+            inner_mod_content.extend(generate_commands(cluster));
+
+            let mut cluster_desc = format!(
+                "Holds types and constants related to the [`{}`](self::{}_CLUSTER) cluster.",
+                cluster.name,
+                cluster.name.to_case(Case::UpperSnake),
+            );
+            if let Some(code) = &cluster.manufacturer_code {
+                cluster_desc += &format!(" It is specific to the manufacturer {code}.");
+            }
+            if let Some(base) = &cluster.base {
+                cluster_desc += &format!(
+                    " It is a variant of the [`{base}`](self::{}_CLUSTER) cluster.",
+                    base.to_case(Case::UpperSnake),
+                );
+                if !cluster.device_types.is_empty() {
+                    cluster_desc += &format!(
+                        " It is used by the device types {}.",
+                        cluster.device_types.join(", "),
+                    );
+                }
+            }
+            let mut command_docs = TokenStream::new();
+            if !cluster.commands.is_empty() {
+                let mut command_table = "".to_string();
+                command_table += "| id | name | direction | fields | mandatory |\n";
+                command_table += "|----|------|-----------|--------|-----------|\n";
+                for command in &cluster.commands {
+                    let fields = command
+                        .fields
+                        .iter()
+                        .map(|x| x.name.as_str())
+                        .collect::<Vec<_>>();
+                    command_table += &format!(
+                        "| {} | [{}]({mod_name}::{}) | {} | {} | {} |\n",
+                        command.id,
+                        command.name,
+                        command.name.to_case(Case::UpperCamel),
+                        command.direction_name(),
+                        fields.join(", "),
+                        if command.mandatory == "M" {
+                            "✅"
+                        } else {
+                            "❌"
+                        },
+                    );
+                }
+                command_docs = quote! {
+                    #[doc = ""]
+                    #[doc = "Command list:"]
+                    #[doc = ""]
+                    #[doc = #command_table]
+                };
+            }
+            mod_content.extend(quote! {
+                #[doc = #cluster_desc]
+                #[doc = ""]
+                #[doc = "Attribute list:"]
+                #[doc = ""]
+                #[doc = #attr_table]
+                #command_docs
+                pub mod #mod_name {
+                    #[allow(unused)]
+                    use ::zcl::types::*;
+                    #[allow(unused)]
+                    use super::*;
+                    #inner_mod_content
+                }
+            });
+            mod_content.extend(generate_cluster(cluster));
+
+            // Variants are reachable through their base cluster only
+            if cluster.base.is_none() {
+                cluster_table.push((
+                    (
+                        parse_id(&cluster.id),
+                        cluster.manufacturer_code.as_deref().map(parse_id),
+                    ),
+                    quote! { #file_mod::#mod_name::DESCRIPTOR },
+                ));
+            }
+        }
+
+        let wrapped_mod = quote! {
+            pub mod #mod_name {
+                #[allow(unused)]
+                use ::zcl::types::*;
+                #mod_content
+            }
+        };
+
+        generated.extend(wrapped_mod);
+    }
+
+    generated.extend(generate_cluster_table(&mut cluster_table));
+    Ok(generated)
+}
+
+/// Formats generated definitions as source code, e.g. to be written to `OUT_DIR`
+/// and included from there.
+pub fn format(tokens: TokenStream) -> String {
+    prettyplease::unparse(&parse_quote! { #tokens })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generates a single definition file with the given contents and returns the error
+    fn error(name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("zcl_codegen_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{name}.txt"));
+        std::fs::write(&path, contents).unwrap();
+        let error = generate([&path]).expect_err("definitions are invalid");
+        format!("{}:{}: {}", error.line, error.column, error.kind)
+    }
+
+    /// Generates a single definition file with the given contents and returns the code
+    fn generated(name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("zcl_codegen_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{name}.txt"));
+        std::fs::write(&path, contents).unwrap();
+        format(generate([&path]).unwrap())
+    }
+
+    #[test]
+    fn unknown_enum_and_bitmap_names() {
+        assert_eq!(
+            error(
+                "unknown_enum",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A enum8:Nope - R 0 O\n}\n"
+            ),
+            "2:17: unknown type 'enum8:Nope'"
+        );
+        assert_eq!(
+            error(
+                "unknown_bitmap",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A map8:Nope - R 0 O\n}\n"
+            ),
+            "2:17: unknown type 'map8:Nope'"
+        );
+        // The width must match the definition
+        assert_eq!(
+            error(
+                "bitmap_width",
+                "bitmap16 B {\n  bit 0 X\n}\ncluster Foo 0x0006 {\n  attr 0x0000 A map8:B - R 0 O\n}\n"
+            ),
+            "5:17: unknown type 'map8:B'"
+        );
+        assert_eq!(
+            error(
+                "unknown_field_enum",
+                "cluster Foo 0x0006 {\n  command 0x00 C received M {\n    field B enum8:Nope\n  }\n}\n"
+            ),
+            "3:13: unknown type 'enum8:Nope'"
+        );
+    }
+
+    #[test]
+    fn duplicate_names() {
+        assert_eq!(
+            error("duplicate_bit", "bitmap8 B {\n  bit 0 X\n  bit 1 X\n}\n"),
+            "3:9: duplicate name 'X'"
+        );
+        assert_eq!(
+            error("duplicate_variant", "enum8 E {\n  0x00 X\n  0x01 X\n}\n"),
+            "3:8: duplicate name 'X'"
+        );
+        assert_eq!(
+            error(
+                "duplicate_cluster",
+                "cluster Foo 0x0006 {\n}\ncluster Foo 0x0007 {\n}\n"
+            ),
+            "3:9: duplicate name 'Foo'"
+        );
+        assert_eq!(
+            error(
+                "duplicate_type",
+                "enum8 E {\n  0x00 X\n}\nbitmap8 E {\n  bit 0 X\n}\n"
+            ),
+            "4:9: duplicate name 'E'"
+        );
+    }
+
+    #[test]
+    fn bounds_must_fit_the_type() {
+        assert_eq!(
+            error(
+                "int8_bound",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A int8 0x00,0x1ff R 0 O\n}\n"
+            ),
+            "2:22: value 0x1ff does not fit into 8 bits"
+        );
+        assert_eq!(
+            error(
+                "uint8_bound",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A uint8 -1,0xff R 0 O\n}\n"
+            ),
+            "2:23: value -1 does not fit into 8 bits"
+        );
+        assert_eq!(
+            error(
+                "int16_bound",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A int16 -32769,100 R 0 O\n}\n"
+            ),
+            "2:23: value -32769 does not fit into 16 bits"
+        );
+    }
+
+    #[test]
+    fn duplicate_attribute_ids() {
+        assert_eq!(
+            error(
+                "duplicate_attribute",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A uint8 - R 0 O\n  attr 0x0000 B uint8 - R 0 O\n}\n"
+            ),
+            "3:8: duplicate attribute ID 0x0000"
+        );
+    }
+
+    #[test]
+    fn unbalanced_braces() {
+        assert_eq!(
+            error("unmatched_brace", "cluster Foo 0x0006 {\n}\n}\n"),
+            "3:1: '}' without an open block"
+        );
+        assert_eq!(
+            error(
+                "unclosed_block",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A uint8 - R 0 O\n"
+            ),
+            "1:1: block is never closed"
+        );
+    }
+
+    #[test]
+    fn undefined_range_reference() {
+        assert_eq!(
+            error(
+                "undefined_reference",
+                "cluster Foo 0x0006 {\n  attr 0x0000 A uint8 Min,0xfe R 0 O\n}\n"
+            ),
+            "2:23: range refers to undefined attribute 'Min'"
+        );
+    }
+
+    #[test]
+    fn optional_fields() {
+        assert_eq!(
+            error(
+                "required_after_optional",
+                "cluster Foo 0x0006 {\n  command 0x00 C received M {\n    field A uint8 optional\n    field B uint8\n  }\n}\n"
+            ),
+            "4:11: required field 'B' follows an optional field"
+        );
+        assert_eq!(
+            error(
+                "optional_string",
+                "cluster Foo 0x0006 {\n  command 0x00 C received M {\n    field A string optional\n  }\n}\n"
+            ),
+            "3:13: expected a fixed-width type for an optional field but found 'string'"
+        );
+        assert_eq!(
+            error(
+                "optional_keyword",
+                "cluster Foo 0x0006 {\n  command 0x00 C received M {\n    field A uint8 maybe\n  }\n}\n"
+            ),
+            "3:19: expected 'optional' but found 'maybe'"
+        );
+    }
+
+    #[test]
+    fn bitmap_fields() {
+        let code = generated(
+            "bitmap_fields",
+            "bitmap8 B {\n  bit 0 X\n  bits 2..4 Y\n}\n",
+        );
+        assert!(code.contains("pub const X: Self = Self(1 << 0);"));
+        // The subfield is shifted down and masked to its three bits
+        assert!(code.contains("(self.0 >> 2) & 0x7"));
+        assert!(code.contains("self.0 = (self.0 & !(0x7 << 2)) | ((value & 0x7) << 2);"));
+        // Bit 1 and the bits above the subfield are reserved
+        assert!(code.contains("pub const ALL: Self = Self(0x1d);"));
+
+        assert_eq!(
+            error(
+                "overlapping_bits",
+                "bitmap8 B {\n  bits 0..2 X\n  bit 2 Y\n}\n"
+            ),
+            "3:7: bits 2 overlap a bit which is already defined"
+        );
+        assert_eq!(
+            error("bit_out_of_range", "bitmap8 B {\n  bits 4..8 X\n}\n"),
+            "2:8: invalid value '4..8'"
+        );
+        assert_eq!(
+            error("empty_bit_range", "bitmap8 B {\n  bits 3..3 X\n}\n"),
+            "2:8: invalid value '3..3'"
+        );
+    }
+
+    #[test]
+    fn enum_width() {
+        let code = generated("enum_width", "enum16 E {\n  0x0000 A\n  0x00ff B\n}\n");
+        assert!(code.contains("#[repr(u16)]"));
+        // 0xff is an ordinary value of a 16-bit enum
+        assert!(code.contains("B = 0x00ff,"));
+        assert!(code.contains("None = 0xffff,"));
+        assert!(code.contains("const NON_VALUE: Self = Self::None;"));
+        assert!(code.contains("impl TryFrom<u16> for E"));
+
+        assert_eq!(
+            error("enum8_overflow", "enum8 E {\n  0x100 A\n}\n"),
+            "2:3: value 0x100 does not fit into 8 bits"
+        );
+        assert_eq!(
+            error("enum16_overflow", "enum16 E {\n  0x10000 A\n}\n"),
+            "2:3: value 0x10000 does not fit into 16 bits"
+        );
+    }
+}